@@ -0,0 +1,329 @@
+use crate::asynchronous::AsyncCrossref;
+use crate::error::{Error, ErrorKind, Result};
+use crate::query::facet::{Facet, FacetCount};
+use crate::query::works::UpdateType;
+use crate::query::ResultControl;
+use crate::response::{FacetResult, FacetValue, WorkList};
+use crate::{WorkListQuery, WorkResultControl, WorksFilter};
+use chrono::NaiveDate;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::de::{value, Deserialize, IntoDeserializer};
+
+/// The facet counts of all works that match a query, without the works themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregation {
+    /// the number of works that match the query
+    pub total_results: usize,
+    /// the typed counts of the facets crossref returned, in the order of their names
+    pub facets: Vec<FacetResult>,
+}
+
+impl Aggregation {
+    /// the counts of the `facet`, if crossref returned it
+    pub fn facet(&self, facet: Facet) -> Option<&FacetResult> {
+        self.facets.iter().find(|result| result.facet == facet)
+    }
+}
+
+impl From<WorkList> for Aggregation {
+    fn from(works: WorkList) -> Self {
+        Aggregation {
+            total_results: works.total_results,
+            facets: works.facet_results(),
+        }
+    }
+}
+
+/// The counts of secondary facets for each value of a primary facet, like the works per year of each funder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossTab {
+    /// the counts of the values of the primary facet over all works that match the query
+    pub primary: FacetResult,
+    /// the aggregation of the works with each value of the primary facet, in the order of `primary`
+    ///
+    /// Values no filter selects, like a `published` value crossref returns as text, have no row.
+    pub rows: Vec<(FacetValue, Aggregation)>,
+}
+
+impl CrossTab {
+    /// the aggregation of the works with the `value` of the primary facet
+    pub fn row(&self, value: &FacetValue) -> Option<&Aggregation> {
+        self.rows
+            .iter()
+            .find(|(v, _)| v == value)
+            .map(|(_, aggregation)| aggregation)
+    }
+
+    /// the number of works with the `value` of the primary facet and the `secondary` value of the `facet`
+    pub fn count(&self, value: &FacetValue, facet: Facet, secondary: &FacetValue) -> Option<usize> {
+        self.row(value)?.facet(facet)?.count(secondary)
+    }
+}
+
+/// how many aggregations of a cross-tab run at the same time
+const CROSS_TAB_CONCURRENCY: usize = 4;
+
+/// the `query` restricted to the counts of the `facets`, without any works
+pub(crate) fn aggregate_query(mut query: WorkListQuery, facets: Vec<FacetCount>) -> WorkListQuery {
+    let works = query.query_mut();
+    works.facets = facets;
+    works.sample = None;
+    works.result_control = Some(WorkResultControl::Standard(ResultControl::Rows(0)));
+    query
+}
+
+/// the filters that restrict a query to the works with the `value` of the `facet`
+///
+/// # Errors
+///
+/// Fails with `UnfilterableFacetValue` if crossref has no filter for the facet, like for `publisher-name`,
+/// or the value is not of the type the facet returns
+pub fn facet_value_filters(facet: Facet, value: &FacetValue) -> Result<Vec<WorksFilter>> {
+    let unfilterable = || {
+        ErrorKind::UnfilterableFacetValue {
+            facet,
+            value: value.to_string(),
+        }
+        .into()
+    };
+    let text = match value {
+        FacetValue::Text(text) => Some(text.clone()),
+        _ => None,
+    };
+    let filters = match (facet, value) {
+        (Facet::Published, FacetValue::Year(year)) => {
+            match (
+                NaiveDate::from_ymd_opt(*year, 1, 1),
+                NaiveDate::from_ymd_opt(*year, 12, 31),
+            ) {
+                (Some(from), Some(until)) => vec![
+                    WorksFilter::FromPubDate(from),
+                    WorksFilter::UntilPubDate(until),
+                ],
+                _ => return Err(unfilterable()),
+            }
+        }
+        (Facet::TypeName, FacetValue::Type(ty)) => vec![WorksFilter::Type(ty.clone())],
+        (Facet::License, FacetValue::Url(url)) => vec![WorksFilter::LicenseUrl(url.to_string())],
+        (Facet::UpdateType, FacetValue::Text(name)) => {
+            let update = UpdateType::deserialize(name.as_str().into_deserializer())
+                .map_err(|_: value::Error| unfilterable())?;
+            vec![WorksFilter::UpdateType(update)]
+        }
+        (Facet::FunderDoi, _) => text.map(WorksFilter::Funder).into_iter().collect(),
+        (Facet::ORCID, _) => text.map(WorksFilter::Orcid).into_iter().collect(),
+        (Facet::ContainerTitle, _) => text.map(WorksFilter::ContainerTitle).into_iter().collect(),
+        (Facet::Assertion, _) => text.map(WorksFilter::Assertion).into_iter().collect(),
+        (Facet::Archive, _) => text.map(WorksFilter::Archive).into_iter().collect(),
+        (Facet::ISSN, _) => text.map(WorksFilter::Issn).into_iter().collect(),
+        (Facet::CategoryName, _) => text.map(WorksFilter::CategoryName).into_iter().collect(),
+        (Facet::RelationType, _) => text.map(WorksFilter::RelationType).into_iter().collect(),
+        (Facet::AssertionGroup, _) => text.map(WorksFilter::AssertionGroup).into_iter().collect(),
+        _ => Vec::new(),
+    };
+    if filters.is_empty() {
+        Err(unfilterable())
+    } else {
+        Ok(filters)
+    }
+}
+
+/// whether `facet_value_filters` has filters for any value of the `facet`
+fn is_filterable(facet: Facet) -> bool {
+    matches!(
+        facet,
+        Facet::Published
+            | Facet::TypeName
+            | Facet::License
+            | Facet::UpdateType
+            | Facet::FunderDoi
+            | Facet::ORCID
+            | Facet::ContainerTitle
+            | Facet::Assertion
+            | Facet::Archive
+            | Facet::ISSN
+            | Facet::CategoryName
+            | Facet::RelationType
+            | Facet::AssertionGroup
+    )
+}
+
+/// runs one aggregation of the `secondary` facets for each value of the `primary` facet
+///
+/// Values no filter selects are left out of the rows, at most `CROSS_TAB_CONCURRENCY` aggregations run at once.
+pub(crate) async fn cross_tab(
+    client: &AsyncCrossref,
+    query: WorkListQuery,
+    primary: FacetCount,
+    secondary: Vec<FacetCount>,
+) -> Result<CrossTab> {
+    let facet = primary.facet;
+    if !is_filterable(facet) {
+        return Err(ErrorKind::UnfilterableFacet { facet }.into());
+    }
+    let primary = client
+        .aggregate(query.clone(), vec![primary])
+        .await?
+        .facet(facet)
+        .cloned()
+        .unwrap_or(FacetResult {
+            facet,
+            value_count: 0,
+            values: Vec::new(),
+        });
+
+    let rows = primary.iter().filter_map(|(value, _)| {
+        let filters = facet_value_filters(facet, value).ok()?;
+        let mut row = query.clone();
+        row.query_mut().filter.extend(filters);
+        Some((value.clone(), row))
+    });
+    let mut rows: Vec<_> = stream::iter(rows.enumerate())
+        .map(|(index, (value, row))| {
+            let secondary = secondary.clone();
+            async move {
+                let aggregation = client.aggregate(row, secondary).await?;
+                Ok::<_, Error>((index, value, aggregation))
+            }
+        })
+        .buffer_unordered(CROSS_TAB_CONCURRENCY)
+        .try_collect()
+        .await?;
+    rows.sort_by_key(|(index, _, _)| *index);
+
+    Ok(CrossTab {
+        rows: rows
+            .into_iter()
+            .map(|(_, value, aggregation)| (value, aggregation))
+            .collect(),
+        primary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{ParamFragment, Type};
+    use crate::{CrossrefRoute, InMemoryTransport, TransportResponse, WorksQuery};
+
+    /// a work list response without items and with the `facets`
+    fn facet_response(total: usize, facets: &str) -> TransportResponse {
+        TransportResponse::ok(format!(
+            r#"{{"status":"ok","message-type":"work-list","message-version":"1.0.0","message":{{"facets":{},"total-results":{},"items-per-page":0,"items":[]}}}}"#,
+            facets, total
+        ))
+    }
+
+    fn facet(facet: Facet) -> FacetCount {
+        FacetCount { facet, count: None }
+    }
+
+    #[test]
+    fn filters_of_facet_values() {
+        let filters = facet_value_filters(Facet::Published, &FacetValue::Year(2019)).unwrap();
+        assert_eq!(
+            vec!["from-pub-date:2019-01-01", "until-pub-date:2019-12-31"],
+            filters
+                .iter()
+                .map(ParamFragment::fragment)
+                .collect::<Vec<_>>()
+        );
+        assert!(facet_value_filters(
+            Facet::PublisherName,
+            &FacetValue::Text("Elsevier BV".to_string())
+        )
+        .is_err());
+        assert!(
+            facet_value_filters(Facet::Published, &FacetValue::Text("n.d.".to_string())).is_err()
+        );
+        assert!(facet_value_filters(
+            Facet::UpdateType,
+            &FacetValue::Text("correction".to_string())
+        )
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn aggregate_and_cross_tab() {
+        let query = WorkListQuery::from(WorksQuery::new("ontologies"));
+        let transport = InMemoryTransport::new();
+        let by_year = aggregate_query(query.clone(), vec![facet(Facet::Published)]);
+        transport.insert(
+            by_year.route().unwrap(),
+            facet_response(
+                5,
+                r#"{"published":{"value-count":3,"values":{"2019":3,"2018":2,"n.d.":1}}}"#,
+            ),
+        );
+        for (year, journal_articles) in &[(2019, 2), (2018, 1)] {
+            let mut row = query.clone();
+            row.query_mut()
+                .filter
+                .extend(facet_value_filters(Facet::Published, &FacetValue::Year(*year)).unwrap());
+            transport.insert(
+                aggregate_query(row, vec![facet(Facet::TypeName)])
+                    .route()
+                    .unwrap(),
+                facet_response(
+                    5,
+                    &format!(
+                        r#"{{"type-name":{{"value-count":1,"values":{{"Journal Article":{}}}}}}}"#,
+                        journal_articles
+                    ),
+                ),
+            );
+        }
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(transport.clone())
+            .build_async()
+            .unwrap();
+
+        let aggregation = client
+            .aggregate(query.clone(), vec![facet(Facet::Published)])
+            .await
+            .unwrap();
+        assert_eq!(5, aggregation.total_results);
+        assert!(transport.requests()[0].url.ends_with("&rows=0"));
+        assert_eq!(
+            Some(3),
+            aggregation
+                .facet(Facet::Published)
+                .unwrap()
+                .count(&FacetValue::Year(2019))
+        );
+
+        let tab = client
+            .cross_tab(query, facet(Facet::Published), vec![facet(Facet::TypeName)])
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![&FacetValue::Year(2019), &FacetValue::Year(2018)],
+            tab.rows.iter().map(|(year, _)| year).collect::<Vec<_>>()
+        );
+        let article = FacetValue::Type(Type::JournalArticle);
+        assert_eq!(
+            Some(2),
+            tab.count(&FacetValue::Year(2019), Facet::TypeName, &article)
+        );
+        assert_eq!(
+            Some(1),
+            tab.count(&FacetValue::Year(2018), Facet::TypeName, &article)
+        );
+        assert_eq!(4, transport.requests().len());
+
+        let err = client
+            .cross_tab(
+                WorksQuery::new("ontologies"),
+                facet(Facet::PublisherName),
+                vec![facet(Facet::TypeName)],
+            )
+            .await
+            .unwrap_err();
+        match err.kind() {
+            ErrorKind::UnfilterableFacet { facet } => assert_eq!(Facet::PublisherName, *facet),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert_eq!(4, transport.requests().len());
+    }
+}
@@ -0,0 +1,1123 @@
+use crate::aggregate::{self, Aggregation, CrossTab};
+use crate::cache::{Cache, Lookup};
+use crate::error::{Error, ErrorKind, Result};
+use crate::harvest::{Harvest, HarvestStream};
+use crate::query::facet::FacetCount;
+use crate::query::{
+    CrossrefQuery, Funders, FundersQuery, Journals, JournalsQuery, Members, MembersQuery, Prefixes,
+    Type, Types,
+};
+use crate::rate_limit::RateLimiter;
+use crate::response::{
+    CrossrefType, Funder, FunderList, Journal, JournalList, Member, MemberList, Message,
+    MessageType, PartialWorkList, Prefix, Response, TypeList, Work, WorkAgency, WorkList,
+};
+use crate::retry::{self, RetryPolicy};
+use crate::sync::{SyncStream, WorksSync};
+use crate::transport::{Transport, TransportRequest};
+use crate::{
+    CrossrefBuilder, WorkListQuery, WorkResultControl, Works, WorksIdentQuery, WorksQuery,
+};
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::task::JoinHandle;
+
+macro_rules! impl_combined_works_query {
+    ($($name:ident  $component:ident,)*) => {
+        $(
+        /// Return one page of the components's `Work` that match the query
+        ///
+        pub async fn $name(&self, ident: WorksIdentQuery) -> Result<WorkList> {
+            let resp = self.get_response(&$component::Works(ident)).await?;
+            get_item!(WorkList, resp.message, resp.message_type)
+        })+
+    };
+}
+
+/// Struct for non-blocking Crossref search API methods
+///
+/// Offers the same methods as the blocking [`crate::Crossref`] client, but each of them returns a future
+/// that has to be driven by a [tokio](https://tokio.rs) runtime.
+/// The client is `Send` and `Sync` and cheap to clone, all clones share the same connection pool.
+///
+/// # Example
+///
+/// ```edition2018
+/// use crossref::{AsyncCrossref, WorksQuery};
+/// # async fn run() -> Result<(), crossref::Error> {
+/// let client = AsyncCrossref::builder().build_async()?;
+///
+/// let work = client.work("10.1037/0003-066X.59.1.29").await?;
+///
+/// let works = client.works(WorksQuery::new("Machine Learning")).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncCrossref {
+    /// use another base url than `api.crossref.org`
+    pub base_url: String,
+    /// executes the requests, by default a `ReqwestTransport`
+    pub transport: Arc<dyn Transport>,
+    /// the headers that are sent with each request
+    pub headers: HeaderMap,
+    /// how failed requests are retried
+    pub retry: RetryPolicy,
+    /// spaces the requests of this client and all its clones
+    pub rate_limiter: RateLimiter,
+    /// reuses the responses to earlier requests, if set
+    pub cache: Option<Cache>,
+}
+
+impl AsyncCrossref {
+    pub(crate) const BASE_URL: &'static str = "https://api.crossref.org";
+
+    /// Constructs a new `CrossrefBuilder`.
+    ///
+    /// Finish it with [`CrossrefBuilder::build_async`] to get an `AsyncCrossref`.
+    pub fn builder() -> CrossrefBuilder {
+        CrossrefBuilder::new()
+    }
+
+    // generate all functions to query combined endpoints
+    impl_combined_works_query!(funder_works Funders, member_works Members,
+    type_works Types, journal_works Journals, prefix_works Prefixes,);
+
+    /// Transforms the `CrossrefQuery` in the request route and  executes the request
+    ///
+    /// # Errors
+    ///
+    /// If it was a bad url, the server will return `Resource not found` a `ResourceNotFound` error will be returned in this case
+    /// Also fails if the json response body could be parsed into `Response`
+    /// Fails if the `Transport` of this client failed to execute the request
+    pub(crate) async fn get_response<T: CrossrefQuery>(&self, query: &T) -> Result<Response> {
+        Ok(serde_json::from_str(&self.get_body(query).await?)?)
+    }
+
+    /// Validates the `query`, executes the request for it and returns the response body
+    ///
+    /// # Errors
+    ///
+    /// Fails with `InvalidQuery` without a request if the `query` breaks a rule of its route
+    /// Fails with `ResourceNotFound` if crossref does not know the resource
+    async fn get_body<T: CrossrefQuery>(&self, query: &T) -> Result<String> {
+        query.validate()?;
+        let resp = self.send(&query.route()?).await?;
+        if resp.starts_with("Resource not found") {
+            Err(ErrorKind::ResourceNotFound {
+                resource: Box::new(query.clone().resource_component()),
+            }
+            .into())
+        } else {
+            Ok(resp)
+        }
+    }
+
+    /// Executes the request for the `route` and returns the response body
+    ///
+    /// A fresh response from the `Cache` of this client is returned without a request, a stale one is revalidated.
+    /// Each attempt waits for the `RateLimiter` of this client first.
+    /// Failed attempts are repeated as long as the `RetryPolicy` of this client allows it.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `RetriesExhausted` if the last response still had a retried status
+    /// Fails if the `Transport` failed to execute the request and either the error can't be retried or no attempts are left
+    async fn send(&self, route: &str) -> Result<String> {
+        let url = &format!("{}{}", self.base_url, route);
+        let mut stale = None;
+        if let Some(cache) = &self.cache {
+            match cache.lookup(url, route)? {
+                Lookup::Fresh(body) => return Ok(body),
+                Lookup::Stale(entry) => stale = Some(entry),
+                Lookup::Miss => {}
+            }
+        }
+        let mut attempt = 1;
+        loop {
+            self.rate_limiter.acquire().await;
+            let mut headers = self.headers.clone();
+            if let Some(entry) = &stale {
+                entry.conditional_headers(&mut headers);
+            }
+            let request = TransportRequest {
+                url: url.to_string(),
+                route: route.to_string(),
+                headers,
+            };
+            let retry_after = match self.transport.send(request).await {
+                Ok(resp) => {
+                    self.rate_limiter.update(&resp.headers);
+                    if let Some(cache) = &self.cache {
+                        if resp.status == 304 {
+                            if let Some(entry) = stale {
+                                return cache.revalidated(entry);
+                            }
+                        }
+                        cache.store(url, &resp)?;
+                    }
+                    if !self.retry.retries_status(resp.status) {
+                        return Ok(resp.body);
+                    }
+                    if !self.retry.has_attempts_left(attempt) {
+                        return Err(ErrorKind::RetriesExhausted {
+                            status: resp.status,
+                            attempts: attempt,
+                        }
+                        .into());
+                    }
+                    retry::retry_after(&resp.headers)
+                }
+                Err(err) => {
+                    if !self.retry.has_attempts_left(attempt) || !RetryPolicy::retries_error(&err) {
+                        return Err(err);
+                    }
+                    None
+                }
+            };
+            tokio::time::sleep(self.retry.delay(attempt, retry_after)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Return the `Work` items that match a certain query.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `works` element expands to a bad route `ResourceNotFound`
+    /// Fails if the response body doesn't have `message` field `MissingMessage`.
+    /// Fails if anything else than a `WorkList` is returned as message `UnexpectedItem`
+    pub async fn works<T: Into<WorkListQuery>>(&self, query: T) -> Result<WorkList> {
+        let resp = self.get_response(&query.into()).await?;
+        get_item!(WorkList, resp.message, resp.message_type)
+    }
+
+    /// Return the `Work` items that match a query that selects only some fields with `WorksQuery::select`
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `works` element expands to a bad route `ResourceNotFound`
+    /// Fails if the response body doesn't have `message` field `MissingMessage`.
+    /// Fails if anything else than a `WorkList` is returned as message `UnexpectedItem`
+    pub async fn partial_works<T: Into<WorkListQuery>>(&self, query: T) -> Result<PartialWorkList> {
+        /// a response whose message is not deserialized yet
+        #[derive(Deserialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct PartialResponse {
+            message_type: MessageType,
+            message: Option<serde_json::Value>,
+        }
+
+        let resp: PartialResponse = serde_json::from_str(&self.get_body(&query.into()).await?)?;
+        match (resp.message_type, resp.message) {
+            (MessageType::WorkList, Some(message)) => Ok(serde_json::from_value(message)?),
+            (MessageType::WorkList, None) => Err(ErrorKind::MissingMessage {
+                expected: MessageType::WorkList,
+            }
+            .into()),
+            (got, _) => Err(ErrorKind::UnexpectedItem {
+                expected: MessageType::WorkList,
+                got,
+            }
+            .into()),
+        }
+    }
+
+    /// Return only the counts of the `facets` of all works that match the `query`
+    ///
+    /// The facets of the `query` are replaced by the `facets` and it requests zero rows,
+    /// so crossref returns no works.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`AsyncCrossref::works`]
+    pub async fn aggregate<T: Into<WorkListQuery>>(
+        &self,
+        query: T,
+        facets: Vec<FacetCount>,
+    ) -> Result<Aggregation> {
+        let works = self
+            .works(aggregate::aggregate_query(query.into(), facets))
+            .await?;
+        Ok(Aggregation::from(works))
+    }
+
+    /// Return the counts of the `secondary` facets for each value of the `primary` facet
+    ///
+    /// Aggregates the `primary` facet first, then runs one aggregation of the `secondary` facets
+    /// for each value of it, restricted to that value by a filter. At most four of these aggregations run at once
+    /// and they share the rate limit of this client. Values no filter selects, like a `published` value crossref
+    /// returns as text, are left out of the rows.
+    ///
+    /// # Example
+    ///
+    /// Count the works of each type per year
+    ///
+    /// ```edition2018
+    /// use crossref::{AsyncCrossref, WorksQuery};
+    /// use crossref::query::facet::{Facet, FacetCount};
+    /// # async fn run() -> Result<(), crossref::Error> {
+    /// let client = AsyncCrossref::builder().build_async()?;
+    ///
+    /// let tab = client
+    ///     .cross_tab(
+    ///         WorksQuery::new("Machine Learning"),
+    ///         FacetCount { facet: Facet::Published, count: Some(10) },
+    ///         vec![FacetCount { facet: Facet::TypeName, count: None }],
+    ///     )
+    ///     .await?;
+    /// for (year, aggregation) in &tab.rows {
+    ///     println!("{}: {:?}", year, aggregation.facet(Facet::TypeName));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with `UnfilterableFacet` without a request if no filter restricts the works to the values of the `primary` facet,
+    /// otherwise like [`AsyncCrossref::works`]
+    pub async fn cross_tab<T: Into<WorkListQuery>>(
+        &self,
+        query: T,
+        primary: FacetCount,
+        secondary: Vec<FacetCount>,
+    ) -> Result<CrossTab> {
+        aggregate::cross_tab(self, query.into(), primary, secondary).await
+    }
+
+    /// Return the `Work` that is identified by  the `doi`.
+    ///
+    /// # Errors
+    /// This method fails if the doi could not identified `ResourceNotFound`
+    ///
+    pub async fn work(&self, doi: &str) -> Result<Work> {
+        let resp = self
+            .get_response(&Works::Identifier(doi.to_string()))
+            .await?;
+        get_item!(Work, resp.message, resp.message_type).map(|x| *x)
+    }
+
+    /// [Deep paging results](https://github.com/CrossRef/rest-api-doc#deep-paging-with-cursors)
+    /// Deep paging is supported for all queries, that return a list of `Work`, `WorkList`.
+    /// This function returns a new stream over pages of `Work`, which is returned as bulk of items as a `WorkList` by crossref.
+    ///
+    /// While a page is handled by the caller, the request for the following page is already in flight.
+    /// At most one page is fetched ahead, so a slow consumer slows down the requests as well.
+    /// The stream has to be polled from within a tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// Iterate over all `Works` linked to search term `Machine Learning`
+    ///
+    /// ```edition2018
+    /// use crossref::{AsyncCrossref, WorksQuery, Work};
+    /// use futures::TryStreamExt;
+    /// # async fn run() -> Result<(), crossref::Error> {
+    /// let client = AsyncCrossref::builder().build_async()?;
+    ///
+    /// let mut works = client.deep_page(WorksQuery::new("Machine Learning")).into_work_stream();
+    ///
+    /// while let Some(work) = works.try_next().await? {
+    ///     println!("{}", work.doi);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deep_page<T: Into<WorkListQuery>>(&self, query: T) -> WorkListStream {
+        WorkListStream {
+            query: query.into(),
+            client: self.clone(),
+            next_page: None,
+            finished: false,
+            completed: false,
+            page: 1,
+            items_seen: 0,
+        }
+    }
+
+    /// Continue deep paging from a `checkpoint` that was taken with [`WorkListStream::checkpoint`]
+    ///
+    /// The first page of the returned stream is the page that followed the last page returned before the checkpoint.
+    ///
+    /// # Example
+    ///
+    /// ```edition2018
+    /// use crossref::{AsyncCrossref, DeepPageCheckpoint, WorksQuery};
+    /// use futures::TryStreamExt;
+    /// # async fn run() -> Result<(), crossref::Error> {
+    /// let client = AsyncCrossref::builder().build_async()?;
+    ///
+    /// let mut pages = client.deep_page(WorksQuery::new("Machine Learning"));
+    /// pages.try_next().await?;
+    /// let checkpoint = serde_json::to_string(&pages.checkpoint())?;
+    ///
+    /// // later, after a restart
+    /// let checkpoint: DeepPageCheckpoint = serde_json::from_str(&checkpoint)?;
+    /// let mut pages = client.resume_deep_page(checkpoint);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resume_deep_page(&self, checkpoint: DeepPageCheckpoint) -> WorkListStream {
+        let DeepPageCheckpoint {
+            mut query,
+            cursor,
+            items_seen,
+            page,
+            finished,
+        } = checkpoint;
+        if let Some(cursor) = cursor {
+            let rows = match query.query().result_control {
+                Some(WorkResultControl::Cursor { rows, .. }) => rows,
+                _ => None,
+            };
+            query.query_mut().result_control = Some(WorkResultControl::Cursor {
+                token: Some(cursor),
+                rows,
+            });
+        }
+        WorkListStream {
+            query,
+            client: self.clone(),
+            next_page: None,
+            finished,
+            completed: finished,
+            page,
+            items_seen,
+        }
+    }
+
+    /// Harvest all works of a query by deep paging disjoint date windows at the same time
+    ///
+    /// See [`Harvest`] for how the windows are chosen. All windows share the rate limit of this client.
+    /// The stream has to be polled from within a tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```edition2018
+    /// use crossref::{AsyncCrossref, Harvest, WorksQuery};
+    /// use chrono::NaiveDate;
+    /// use futures::TryStreamExt;
+    /// # async fn run() -> Result<(), crossref::Error> {
+    /// let client = AsyncCrossref::builder().build_async()?;
+    ///
+    /// let mut works = client.harvest(Harvest::new(
+    ///     WorksQuery::new("Machine Learning"),
+    ///     NaiveDate::from_ymd_opt(2019, 1, 1).unwrap(),
+    ///     NaiveDate::from_ymd_opt(2019, 6, 30).unwrap(),
+    /// ));
+    ///
+    /// while let Some(work) = works.try_next().await? {
+    ///     println!("{}", work.doi);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn harvest(&self, harvest: Harvest) -> HarvestStream {
+        HarvestStream::new(self.clone(), harvest)
+    }
+
+    /// Page through all works of a query that changed since the last sync
+    ///
+    /// See [`WorksSync`] for how changed works are selected and classified.
+    /// The stream has to be polled from within a tokio runtime.
+    pub fn sync(&self, sync: WorksSync) -> SyncStream {
+        SyncStream::new(self, sync)
+    }
+
+    /// Return the `Agency` that registers the `Work` identified by  the `doi`.
+    ///
+    /// # Errors
+    /// This method fails if the doi could not identified `ResourceNotFound`
+    ///
+    pub async fn work_agency(&self, doi: &str) -> Result<WorkAgency> {
+        let resp = self.get_response(&Works::Agency(doi.to_string())).await?;
+        get_item!(WorkAgency, resp.message, resp.message_type)
+    }
+
+    /// Return the matching `Funders` items.
+    pub async fn funders(&self, funders: FundersQuery) -> Result<FunderList> {
+        let resp = self.get_response(&Funders::Query(funders)).await?;
+        get_item!(FunderList, resp.message, resp.message_type)
+    }
+
+    /// Return the `Funder` for the `id`
+    pub async fn funder(&self, id: &str) -> Result<Funder> {
+        let resp = self
+            .get_response(&Funders::Identifier(id.to_string()))
+            .await?;
+        get_item!(Funder, resp.message, resp.message_type).map(|x| *x)
+    }
+
+    /// Return the matching `Members` items.
+    pub async fn members(&self, members: MembersQuery) -> Result<MemberList> {
+        let resp = self.get_response(&Members::Query(members)).await?;
+        get_item!(MemberList, resp.message, resp.message_type)
+    }
+
+    /// Return the `Member` for the `id`
+    pub async fn member(&self, member_id: &str) -> Result<Member> {
+        let resp = self
+            .get_response(&Members::Identifier(member_id.to_string()))
+            .await?;
+        get_item!(Member, resp.message, resp.message_type).map(|x| *x)
+    }
+
+    /// Return the `Prefix` for the `id`
+    pub async fn prefix(&self, id: &str) -> Result<Prefix> {
+        let resp = self
+            .get_response(&Prefixes::Identifier(id.to_string()))
+            .await?;
+        get_item!(Prefix, resp.message, resp.message_type)
+    }
+
+    /// Return the matching `Journals` items.
+    pub async fn journals(&self, journals: JournalsQuery) -> Result<JournalList> {
+        let resp = self.get_response(&Journals::Query(journals)).await?;
+        get_item!(JournalList, resp.message, resp.message_type)
+    }
+
+    /// Return a specific `Journal`
+    pub async fn journal(&self, id: &str) -> Result<Journal> {
+        let resp = self
+            .get_response(&Journals::Identifier(id.to_string()))
+            .await?;
+        get_item!(Journal, resp.message, resp.message_type).map(|x| *x)
+    }
+
+    /// Return all available `Type`
+    pub async fn types(&self) -> Result<TypeList> {
+        let resp = self.get_response(&Types::All).await?;
+        get_item!(TypeList, resp.message, resp.message_type)
+    }
+
+    /// Return the `Type` for the `id`
+    pub async fn type_(&self, id: &Type) -> Result<CrossrefType> {
+        let resp = self
+            .get_response(&Types::Identifier(id.id().to_string()))
+            .await?;
+        get_item!(Type, resp.message, resp.message_type)
+    }
+
+    /// Get a random set of DOIs
+    pub async fn random_dois(&self, len: usize) -> Result<Vec<String>> {
+        self.works(WorksQuery::random(len))
+            .await
+            .map(|x| x.items.into_iter().map(|x| x.doi).collect())
+    }
+}
+
+/// The state of deep paging, to continue where it stopped
+///
+/// Taken with [`WorkListStream::checkpoint`] or [`crate::WorkListIterator::checkpoint`]
+/// and continued with [`AsyncCrossref::resume_deep_page`] or [`crate::Crossref::resume_deep_page`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeepPageCheckpoint {
+    /// the query deep paging was started with, without a cursor token
+    pub query: WorkListQuery,
+    /// the cursor token for the page that is requested next, `None` if no page was returned yet
+    pub cursor: Option<String>,
+    /// the number of items of all pages returned so far
+    pub items_seen: usize,
+    /// the number of the page that is returned next, starting at 1
+    pub page: usize,
+    /// whether all pages were returned
+    pub finished: bool,
+}
+
+/// Allows streaming the pages of a deep page work request
+///
+/// Created by [`AsyncCrossref::deep_page`].
+pub struct WorkListStream {
+    /// the query
+    query: WorkListQuery,
+    /// performs each request
+    client: AsyncCrossref,
+    /// the request for the page that is returned next, already in flight
+    next_page: Option<JoinHandle<Result<WorkList>>>,
+    /// whether no more requests should be made
+    finished: bool,
+    /// whether all pages were returned, unlike `finished` not set after an error
+    completed: bool,
+    /// the number of the page that is returned next, starting at 1
+    page: usize,
+    /// the number of items of all pages returned so far
+    items_seen: usize,
+}
+
+impl WorkListStream {
+    /// convenience method to create a stream over the `Work` items of all pages
+    pub fn into_work_stream(self) -> impl Stream<Item = Result<Work>> {
+        self.map_ok(|list| stream::iter(list.items.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// the number of the page that is returned next, starting at 1
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// the cursor token the request for the next page is made with, if deep paging with a cursor
+    pub fn cursor(&self) -> Option<&str> {
+        match &self.query.query().result_control {
+            Some(WorkResultControl::Cursor { token, .. }) => token.as_deref().or(Some("*")),
+            _ => None,
+        }
+    }
+
+    /// the number of items of all pages returned so far
+    pub fn items_seen(&self) -> usize {
+        self.items_seen
+    }
+
+    /// the state of this stream, which can be persisted and continued with [`AsyncCrossref::resume_deep_page`]
+    ///
+    /// The checkpoint points to the page that is returned next, a prefetched page is requested again after resuming.
+    pub fn checkpoint(&self) -> DeepPageCheckpoint {
+        let mut query = self.query.clone();
+        let cursor = match &mut query.query_mut().result_control {
+            Some(WorkResultControl::Cursor { token, .. }) => token.take(),
+            _ => None,
+        };
+        DeepPageCheckpoint {
+            query,
+            cursor,
+            items_seen: self.items_seen,
+            page: self.page,
+            finished: self.completed,
+        }
+    }
+
+    /// wraps the `error` of the request for the current page in a `DeepPage` error
+    fn page_error(&self, error: Error) -> Error {
+        ErrorKind::DeepPage {
+            page: self.page,
+            cursor: self.cursor().map(str::to_string),
+            error: Box::new(error),
+        }
+        .into()
+    }
+
+    /// starts the request for the page the current query points to
+    fn fetch_next_page(&mut self) {
+        let client = self.client.clone();
+        let query = self.query.clone();
+        self.next_page = Some(tokio::spawn(async move {
+            let resp = client.get_response(&query).await?;
+            get_item!(WorkList, resp.message, resp.message_type)
+        }));
+    }
+}
+
+impl Stream for WorkListStream {
+    type Item = Result<WorkList>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.next_page.is_none() {
+            if this.finished {
+                return Poll::Ready(None);
+            }
+            // if no result control is set, set a new cursor
+            let control = &mut this.query.query_mut().result_control;
+            if control.is_none() {
+                *control = Some(WorkResultControl::new_cursor());
+            }
+            this.fetch_next_page();
+        }
+
+        let page = match this.next_page.as_mut() {
+            Some(handle) => match Pin::new(handle).poll(cx) {
+                Poll::Ready(page) => page,
+                Poll::Pending => return Poll::Pending,
+            },
+            None => return Poll::Ready(None),
+        };
+        this.next_page = None;
+
+        let worklist = match page {
+            Ok(Ok(worklist)) => worklist,
+            Ok(Err(err)) => {
+                this.finished = true;
+                return Poll::Ready(Some(Err(this.page_error(err))));
+            }
+            Err(err) => {
+                this.finished = true;
+                if err.is_panic() {
+                    std::panic::resume_unwind(err.into_panic());
+                }
+                let cancelled = ErrorKind::Cancelled {
+                    msg: err.to_string(),
+                };
+                return Poll::Ready(Some(Err(this.page_error(cancelled.into()))));
+            }
+        };
+
+        if worklist.items.is_empty() {
+            this.finished = true;
+            this.completed = true;
+            return Poll::Ready(None);
+        }
+        this.page += 1;
+        this.items_seen += worklist.items.len();
+
+        if let Some(cursor) = &worklist.next_cursor {
+            match &mut this.query.query_mut().result_control {
+                Some(WorkResultControl::Cursor { token, .. }) => {
+                    // use the received cursor token for the next page and prefetch it
+                    *token = Some(cursor.clone());
+                    this.fetch_next_page();
+                }
+                _ => {
+                    // standard result control was set, don't deep page
+                    this.finished = true;
+                    this.completed = true;
+                }
+            }
+        } else {
+            // no cursor received, this is the last page
+            this.finished = true;
+            this.completed = true;
+        }
+
+        Poll::Ready(Some(Ok(worklist)))
+    }
+}
+
+impl Drop for WorkListStream {
+    fn drop(&mut self) {
+        // don't leave a prefetched request running
+        if let Some(handle) = self.next_page.take() {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{Component, CrossrefRoute, ResultControl};
+    use crate::rate_limit::RateLimit;
+    use crate::transport::tests::{work_list_response, work_response};
+    use crate::transport::{FixtureTransport, InMemoryTransport, TransportResponse};
+    use crate::WorkField;
+    use reqwest::header::{HeaderValue, USER_AGENT};
+    use std::time::Duration;
+
+    fn client(transport: &InMemoryTransport) -> AsyncCrossref {
+        AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(transport.clone())
+            .build_async()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn work_from_transport() {
+        let transport = InMemoryTransport::new()
+            .with_json("/works/10.5555/12345678", work_response("10.5555/12345678"));
+        let work = client(&transport).work("10.5555/12345678").await.unwrap();
+        assert_eq!("10.5555/12345678", work.doi);
+
+        let requests = transport.requests();
+        assert_eq!(1, requests.len());
+        assert_eq!(
+            "https://api.crossref.org/works/10.5555/12345678",
+            requests[0].url
+        );
+        assert_eq!(
+            Some(&HeaderValue::from_static("mailto:polite@example.com")),
+            requests[0].headers.get(USER_AGENT)
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_query_is_not_sent() {
+        let transport = InMemoryTransport::new();
+        let client = client(&transport);
+        let err = client
+            .works(
+                WorksQuery::new("ontologies")
+                    .result_control(WorkResultControl::Standard(ResultControl::Rows(2000))),
+            )
+            .await
+            .unwrap_err();
+        match err.kind() {
+            ErrorKind::InvalidQuery { violation } => {
+                assert_eq!(&crate::Violation::TooManyRows { rows: 2000 }, violation)
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(client
+            .members(MembersQuery::new("hindawi").result_control(ResultControl::Sample(10)))
+            .await
+            .is_err());
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn journals_from_transport() {
+        let transport = InMemoryTransport::new().with_json(
+            "/journals?query=nature&rows=1",
+            r#"{"status":"ok","message-type":"journal-list","message-version":"1.0.0","message":{"items-per-page":1,"total-results":2,"items":[{"title":"Nature","publisher":"Springer Nature","subjects":[],"ISSN":["0028-0836"],"issn-type":[]}]}}"#,
+        );
+        let journals = client(&transport)
+            .journals(JournalsQuery::new("nature").result_control(ResultControl::Rows(1)))
+            .await
+            .unwrap();
+        assert_eq!(2, journals.total_results);
+        assert_eq!(Some("Nature".to_string()), journals.items[0].title);
+    }
+
+    #[tokio::test]
+    async fn unknown_route_not_found() {
+        let transport = InMemoryTransport::new();
+        let err = client(&transport)
+            .work("10.5555/missing")
+            .await
+            .unwrap_err();
+        match err.kind() {
+            ErrorKind::ResourceNotFound { .. } => {}
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[tokio::test]
+    async fn deep_page_follows_cursor() {
+        let query = WorksQuery::new("ontologies");
+        let first = WorkListQuery::from(query.clone().new_cursor())
+            .route()
+            .unwrap();
+        let second = WorkListQuery::from(query.clone().next_cursor("abc"))
+            .route()
+            .unwrap();
+        let last = WorkListQuery::from(query.clone().next_cursor("def"))
+            .route()
+            .unwrap();
+        let transport = InMemoryTransport::new()
+            .with_json(
+                first,
+                work_list_response(&["10.5555/1", "10.5555/2"], Some("abc")),
+            )
+            .with_json(second, work_list_response(&["10.5555/3"], Some("def")))
+            .with_json(last, work_list_response(&[], Some("ghi")));
+
+        let dois: Vec<_> = client(&transport)
+            .deep_page(query)
+            .into_work_stream()
+            .map_ok(|work| work.doi)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(vec!["10.5555/1", "10.5555/2", "10.5555/3"], dois);
+        assert_eq!(3, transport.requests().len());
+    }
+
+    #[tokio::test]
+    async fn deep_page_reports_cancelled_page() {
+        use futures::StreamExt;
+
+        let mut pages = client(&InMemoryTransport::new()).deep_page(WorksQuery::new("ontologies"));
+        let handle = tokio::spawn(futures::future::pending::<Result<WorkList>>());
+        handle.abort();
+        pages.next_page = Some(handle);
+
+        let err = pages.next().await.unwrap().unwrap_err();
+        match err.kind() {
+            ErrorKind::DeepPage { page, error, .. } => {
+                assert_eq!(1, *page);
+                assert!(matches!(error.kind(), ErrorKind::Cancelled { .. }));
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert!(pages.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn partial_works_from_transport() {
+        let query = WorksQuery::new("ontologies").select(vec![WorkField::Doi, WorkField::Title]);
+        let transport = InMemoryTransport::new().with_json(
+            WorkListQuery::from(query.clone()).route().unwrap(),
+            r#"{"status":"ok","message-type":"work-list","message-version":"1.0.0","message":{"facets":{},"total-results":1,"items-per-page":20,"items":[{"DOI":"10.5555/1","title":["Ontologies"]}]}}"#,
+        );
+        let client = client(&transport);
+
+        let works = client.partial_works(query.clone()).await.unwrap();
+        assert_eq!(Some("10.5555/1".to_string()), works.items[0].doi);
+        assert!(works.items[0].publisher.is_none());
+        // the same response lacks the fields a complete `Work` requires
+        assert!(client.works(query).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn retries_with_transport() {
+        let transport = InMemoryTransport::new();
+        transport.insert("/types/book", TransportResponse::with_status(503, ""));
+        transport.insert(
+            "/types/book",
+            TransportResponse::ok(
+                r#"{"status":"ok","message-type":"type","message-version":"1.0.0","message":{"id":"book","label":"Book"}}"#,
+            ),
+        );
+        let client = AsyncCrossref::builder()
+            .transport(transport.clone())
+            .retry(
+                RetryPolicy::default()
+                    .base_delay(Duration::from_millis(1))
+                    .jitter(Duration::from_millis(0)),
+            )
+            .build_async()
+            .unwrap();
+
+        let book = client.type_(&Type::Book).await.unwrap();
+        assert_eq!("book", book.id);
+        assert_eq!(2, transport.requests().len());
+    }
+
+    #[tokio::test]
+    async fn default_client_does_not_retry() {
+        let transport = InMemoryTransport::new();
+        transport.insert("/types/book", TransportResponse::with_status(500, ""));
+
+        let err = client(&transport).type_(&Type::Book).await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Serde { .. }));
+        assert_eq!(1, transport.requests().len());
+    }
+
+    #[tokio::test]
+    async fn learns_rate_limit_from_transport() {
+        let mut resp = TransportResponse::ok(work_response("10.5555/1"));
+        resp.headers
+            .insert("x-rate-limit-limit", HeaderValue::from_static("50"));
+        resp.headers
+            .insert("x-rate-limit-interval", HeaderValue::from_static("1s"));
+        let transport = InMemoryTransport::new();
+        transport.insert("/works/10.5555/1", resp);
+
+        let client = client(&transport);
+        client.work("10.5555/1").await.unwrap();
+        assert_eq!(Some(RateLimit::per_second(50)), client.rate_limiter.limit());
+    }
+
+    #[tokio::test]
+    async fn replay_recorded_deep_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let query = WorksQuery::new("ontologies");
+        let first = WorkListQuery::from(query.clone().new_cursor())
+            .route()
+            .unwrap();
+        let last = WorkListQuery::from(query.clone().next_cursor("abc"))
+            .route()
+            .unwrap();
+        let transport = InMemoryTransport::new()
+            .with_json(
+                first,
+                work_list_response(&["10.5555/1", "10.5555/2"], Some("abc")),
+            )
+            .with_json(last, work_list_response(&[], None));
+
+        let recording = AsyncCrossref::builder()
+            .transport(transport)
+            .record(dir.path())
+            .build_async()
+            .unwrap();
+        let recorded: Vec<_> = recording
+            .deep_page(query.clone())
+            .into_work_stream()
+            .map_ok(|work| work.doi)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(2, std::fs::read_dir(dir.path()).unwrap().count());
+
+        let replaying = AsyncCrossref::builder()
+            .replay(dir.path())
+            .build_async()
+            .unwrap();
+        let replayed: Vec<_> = replaying
+            .deep_page(query)
+            .into_work_stream()
+            .map_ok(|work| work.doi)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(recorded, replayed);
+
+        let err = replaying.work("10.5555/1").await.unwrap_err();
+        match err.kind() {
+            ErrorKind::MissingFixture { url, .. } => {
+                assert_eq!("https://api.crossref.org/works/10.5555/1", url)
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_responses() {
+        let transport =
+            InMemoryTransport::new().with_json("/works/10.5555/1", work_response("10.5555/1"));
+        let client = AsyncCrossref::builder()
+            .transport(transport.clone())
+            .cache(Cache::in_memory())
+            .build_async()
+            .unwrap();
+
+        client.work("10.5555/1").await.unwrap();
+        let work = client.work("10.5555/1").await.unwrap();
+        assert_eq!("10.5555/1", work.doi);
+        assert_eq!(1, transport.requests().len());
+    }
+
+    #[tokio::test]
+    async fn revalidates_stale_responses() {
+        let mut resp = TransportResponse::ok(work_response("10.5555/1"));
+        resp.headers
+            .insert(reqwest::header::ETAG, HeaderValue::from_static("\"v1\""));
+        let transport = InMemoryTransport::new();
+        transport.insert("/works/10.5555/1", resp);
+        transport.insert("/works/10.5555/1", TransportResponse::with_status(304, ""));
+        let client = AsyncCrossref::builder()
+            .transport(transport.clone())
+            .cache(Cache::in_memory().ttl(Duration::from_secs(0)))
+            .build_async()
+            .unwrap();
+
+        client.work("10.5555/1").await.unwrap();
+        let work = client.work("10.5555/1").await.unwrap();
+        assert_eq!("10.5555/1", work.doi);
+
+        let requests = transport.requests();
+        assert_eq!(2, requests.len());
+        assert_eq!(
+            Some(&HeaderValue::from_static("\"v1\"")),
+            requests[1].headers.get(reqwest::header::IF_NONE_MATCH)
+        );
+    }
+
+    #[tokio::test]
+    async fn mirror_receives_same_routes() {
+        let transport =
+            InMemoryTransport::new().with_json("/works/10.5555/1", work_response("10.5555/1"));
+        let client = AsyncCrossref::builder()
+            .base_url("http://localhost:8080/")
+            .header("X-Mirror-Key", "secret")
+            .transport(transport.clone())
+            .build_async()
+            .unwrap();
+
+        client.work("10.5555/1").await.unwrap();
+        let requests = transport.requests();
+        assert_eq!("http://localhost:8080/works/10.5555/1", requests[0].url);
+        assert_eq!(
+            Some(&HeaderValue::from_static("secret")),
+            requests[0].headers.get("x-mirror-key")
+        );
+    }
+
+    #[tokio::test]
+    async fn mirror_with_path_keeps_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner =
+            InMemoryTransport::new().with_json("/works/10.5555/1", work_response("10.5555/1"));
+        let transport = FixtureTransport::record(dir.path(), inner.clone());
+        let client = AsyncCrossref::builder()
+            .base_url("https://mirror.example.com/crossref")
+            .cache(Cache::in_memory().component_ttl(Component::Works, Duration::from_secs(0)))
+            .transport(transport.clone())
+            .build_async()
+            .unwrap();
+
+        client.work("10.5555/1").await.unwrap();
+        client.work("10.5555/1").await.unwrap();
+        let requests = inner.requests();
+        // the works ttl applies, so the cached response is revalidated
+        assert_eq!(2, requests.len());
+        assert_eq!(
+            "https://mirror.example.com/crossref/works/10.5555/1",
+            requests[0].url
+        );
+        assert_eq!("/works/10.5555/1", requests[0].route);
+        assert!(transport.fixture_path("/works/10.5555/1").exists());
+    }
+
+    #[tokio::test]
+    async fn deep_page_reports_failed_page() {
+        let query = WorksQuery::new("ontologies");
+        let first = WorkListQuery::from(query.clone().new_cursor())
+            .route()
+            .unwrap();
+        let second = WorkListQuery::from(query.clone().next_cursor("abc"))
+            .route()
+            .unwrap();
+        let transport = InMemoryTransport::new()
+            .with_json(first, work_list_response(&["10.5555/1"], Some("abc")));
+        transport.insert(second, TransportResponse::with_status(500, ""));
+
+        let mut pages = client(&transport).deep_page(query);
+        assert_eq!(1, pages.try_next().await.unwrap().unwrap().items.len());
+        let err = pages.try_next().await.unwrap_err();
+        match err.kind() {
+            ErrorKind::DeepPage {
+                page,
+                cursor,
+                error,
+            } => {
+                assert_eq!(2, *page);
+                assert_eq!(Some("abc"), cursor.as_deref());
+                // the default client does not retry, so the empty body fails to parse
+                assert!(matches!(error.kind(), ErrorKind::Serde { .. }));
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert!(pages.try_next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn resume_from_checkpoint() {
+        let query = WorksQuery::new("ontologies").into_combined_query::<Members>("98");
+        let mut pages = query.clone();
+        pages.query_mut().result_control = Some(WorkResultControl::new_cursor());
+        let first = pages.route().unwrap();
+        pages.query_mut().result_control = Some(WorkResultControl::cursor("abc"));
+        let second = pages.route().unwrap();
+        pages.query_mut().result_control = Some(WorkResultControl::cursor("def"));
+        let last = pages.route().unwrap();
+
+        let transport = InMemoryTransport::new().with_json(
+            first,
+            work_list_response(&["10.5555/1", "10.5555/2"], Some("abc")),
+        );
+        transport.insert(second.clone(), TransportResponse::with_status(503, ""));
+        transport.insert(
+            second,
+            TransportResponse::ok(work_list_response(&["10.5555/3"], Some("def"))),
+        );
+        transport.insert(last, TransportResponse::ok(work_list_response(&[], None)));
+        let client = client(&transport);
+
+        let mut stream = client.deep_page(query);
+        stream.try_next().await.unwrap().unwrap();
+        assert!(stream.try_next().await.is_err());
+
+        let checkpoint = serde_json::to_string(&stream.checkpoint()).unwrap();
+        let checkpoint: DeepPageCheckpoint = serde_json::from_str(&checkpoint).unwrap();
+        assert_eq!(Some("abc"), checkpoint.cursor.as_deref());
+        assert_eq!(2, checkpoint.items_seen);
+        assert_eq!(2, checkpoint.page);
+        assert!(!checkpoint.finished);
+
+        let mut resumed = client.resume_deep_page(checkpoint);
+        let page = resumed.try_next().await.unwrap().unwrap();
+        assert_eq!("10.5555/3", page.items[0].doi);
+        assert!(resumed.try_next().await.unwrap().is_none());
+        assert_eq!(3, resumed.items_seen());
+        assert!(resumed.checkpoint().finished);
+    }
+}
@@ -0,0 +1,403 @@
+use crate::error::Result;
+use crate::query::Component;
+use crate::transport::{fixture_name, TransportResponse};
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where a `Cache` keeps its responses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheStorage {
+    /// keep responses in memory, shared by all clones of the client
+    Memory,
+    /// keep each response in a file in the directory, so later runs can reuse them
+    Disk(PathBuf),
+}
+
+/// Caches the response bodies of successful requests, keyed by the request url
+///
+/// A cached response is used without a request as long as it is younger than the ttl of its component.
+/// Once it is stale and crossref sent an `ETag` or `Last-Modified` header with it, the request is made
+/// conditional. If crossref answers with `304 Not Modified`, the cached response is used and its age is reset.
+///
+/// A cache in memory keeps at most `max_entries` responses, once it is full the response that was stored or
+/// revalidated longest ago is dropped for a new one. A cache on disk is not limited.
+///
+/// The component of a request is the first segment of its route, except for routes that list works of
+/// another component, like `/members/{id}/works`, which count as `Component::Works`.
+///
+/// # Example
+///
+/// Keep single works for a day and everything else for an hour
+///
+/// ```edition2018
+/// # #[cfg(feature = "blocking")] {
+/// use crossref::{Cache, Component, Crossref};
+/// use std::time::Duration;
+/// # fn run() -> Result<(), crossref::Error> {
+/// let client = Crossref::builder()
+///     .cache(
+///         Cache::on_disk(".crossref-cache")
+///             .ttl(Duration::from_secs(60 * 60))
+///             .component_ttl(Component::Works, Duration::from_secs(24 * 60 * 60)),
+///     )
+///     .build()?;
+/// # Ok(())
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cache {
+    /// where the responses are kept
+    pub storage: CacheStorage,
+    /// how long a response is used without revalidation, unless its component has its own ttl
+    pub ttl: Duration,
+    /// the ttl for the responses of a component
+    pub component_ttls: HashMap<Component, Duration>,
+    /// whether to ignore cached responses and always make the request, responses are still stored
+    pub bypass: bool,
+    /// how many responses are kept in memory at most
+    pub max_entries: usize,
+    /// the responses when stored in memory
+    entries: Arc<Mutex<HashMap<String, CacheEntry>>>,
+}
+
+impl Cache {
+    /// the ttl that is used if nothing else was configured
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+    /// the number of responses kept in memory if nothing else was configured
+    pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+    /// create a new cache that keeps the responses in memory
+    pub fn in_memory() -> Self {
+        Cache::new(CacheStorage::Memory)
+    }
+
+    /// create a new cache that keeps the responses in files in `dir`
+    pub fn on_disk<P: Into<PathBuf>>(dir: P) -> Self {
+        Cache::new(CacheStorage::Disk(dir.into()))
+    }
+
+    /// create a new cache that keeps the responses in the `storage`
+    pub fn new(storage: CacheStorage) -> Self {
+        Cache {
+            storage,
+            ttl: Cache::DEFAULT_TTL,
+            component_ttls: HashMap::new(),
+            bypass: false,
+            max_entries: Cache::DEFAULT_MAX_ENTRIES,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// set the ttl for all components without their own ttl
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// set the ttl for the responses of the `component`
+    pub fn component_ttl(mut self, component: Component, ttl: Duration) -> Self {
+        self.component_ttls.insert(component, ttl);
+        self
+    }
+
+    /// set how many responses are kept in memory at most, at least one
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// whether to ignore cached responses and always make the request
+    ///
+    /// The responses of bypassed requests still replace the cached ones.
+    pub fn bypass(mut self, bypass: bool) -> Self {
+        self.bypass = bypass;
+        self
+    }
+
+    /// the ttl for the response to the `route`
+    fn ttl_for(&self, route: &str) -> Duration {
+        component_of(route)
+            .and_then(|component| self.component_ttls.get(&component))
+            .cloned()
+            .unwrap_or(self.ttl)
+    }
+
+    /// looks up the cached response for the `url`, the `route` of the url decides its ttl
+    pub(crate) fn lookup(&self, url: &str, route: &str) -> Result<Lookup> {
+        if self.bypass {
+            return Ok(Lookup::Miss);
+        }
+        let entry = match self.load(url)? {
+            Some(entry) => entry,
+            None => return Ok(Lookup::Miss),
+        };
+        if entry.age() < self.ttl_for(route) {
+            Ok(Lookup::Fresh(entry.body))
+        } else {
+            Ok(Lookup::Stale(entry))
+        }
+    }
+
+    /// stores the `response` to the `url` if it can be reused
+    pub(crate) fn store(&self, url: &str, response: &TransportResponse) -> Result<()> {
+        if response.status != 200 {
+            return Ok(());
+        }
+        let header = |name| {
+            response
+                .headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        self.save(CacheEntry {
+            url: url.to_string(),
+            body: response.body.clone(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            stored_at: now(),
+        })
+    }
+
+    /// resets the age of the stale `entry` after crossref confirmed it is unchanged, returns its body
+    pub(crate) fn revalidated(&self, mut entry: CacheEntry) -> Result<String> {
+        entry.stored_at = now();
+        let body = entry.body.clone();
+        self.save(entry)?;
+        Ok(body)
+    }
+
+    fn load(&self, url: &str) -> Result<Option<CacheEntry>> {
+        match &self.storage {
+            CacheStorage::Memory => Ok(self.lock().get(url).cloned()),
+            CacheStorage::Disk(dir) => {
+                let content = match fs::read_to_string(dir.join(fixture_name(url))) {
+                    Ok(content) => content,
+                    Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                    Err(err) => return Err(err.into()),
+                };
+                // a corrupt or colliding file is treated like a missing one and overwritten
+                Ok(serde_json::from_str::<CacheEntry>(&content)
+                    .ok()
+                    .filter(|entry| entry.url == url))
+            }
+        }
+    }
+
+    fn save(&self, entry: CacheEntry) -> Result<()> {
+        match &self.storage {
+            CacheStorage::Memory => {
+                let mut entries = self.lock();
+                if entries.len() >= self.max_entries && !entries.contains_key(&entry.url) {
+                    let oldest = entries
+                        .values()
+                        .min_by_key(|entry| entry.stored_at)
+                        .map(|entry| entry.url.clone());
+                    if let Some(oldest) = oldest {
+                        entries.remove(&oldest);
+                    }
+                }
+                entries.insert(entry.url.clone(), entry);
+            }
+            CacheStorage::Disk(dir) => {
+                fs::create_dir_all(dir)?;
+                fs::write(
+                    dir.join(fixture_name(&entry.url)),
+                    serde_json::to_string(&entry)?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// the result of looking up a url in the `Cache`
+#[derive(Debug)]
+pub(crate) enum Lookup {
+    /// nothing is cached
+    Miss,
+    /// the cached body can be used as is
+    Fresh(String),
+    /// the entry has to be revalidated first
+    Stale(CacheEntry),
+}
+
+/// a cached response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    /// the url of the request
+    url: String,
+    /// the response body
+    body: String,
+    /// the `ETag` header of the response
+    etag: Option<String>,
+    /// the `Last-Modified` header of the response
+    last_modified: Option<String>,
+    /// when the response was stored or revalidated, in seconds since the unix epoch
+    stored_at: u64,
+}
+
+impl CacheEntry {
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.stored_at))
+    }
+
+    /// adds the headers that make a request for this entry conditional
+    pub(crate) fn conditional_headers(&self, headers: &mut HeaderMap) {
+        if let Some(etag) = self
+            .etag
+            .as_ref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = self
+            .last_modified
+            .as_ref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, modified);
+        }
+    }
+}
+
+/// the component the `route` returns items of
+fn component_of(route: &str) -> Option<Component> {
+    let path = route.split('?').next().unwrap_or("");
+    let segments: Vec<_> = path.trim_start_matches('/').split('/').collect();
+    if segments.get(2) == Some(&"works") {
+        return Some(Component::Works);
+    }
+    match segments[0] {
+        "works" => Some(Component::Works),
+        "funders" => Some(Component::Funders),
+        "prefixes" => Some(Component::Prefixes),
+        "members" => Some(Component::Members),
+        "types" => Some(Component::Types),
+        "journals" => Some(Component::Journals),
+        _ => None,
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components_of_routes() {
+        assert_eq!(Some(Component::Works), component_of("/works/10.5555/1"));
+        assert_eq!(
+            Some(Component::Works),
+            component_of("/members/98/works?rows=1")
+        );
+        assert_eq!(Some(Component::Funders), component_of("/funders/100000015"));
+        assert_eq!(Some(Component::Types), component_of("/types"));
+        assert_eq!(None, component_of(""));
+    }
+
+    #[test]
+    fn ttl_per_component() {
+        let cache = Cache::in_memory()
+            .ttl(Duration::from_secs(10))
+            .component_ttl(Component::Works, Duration::from_secs(0));
+        // the path of a mirror's base url does not change the component
+        let url = "https://mirror.example.com/crossref/works/10.5555/1";
+        cache.store(url, &TransportResponse::ok("{}")).unwrap();
+        match cache.lookup(url, "/works/10.5555/1").unwrap() {
+            Lookup::Stale(entry) => assert_eq!("{}", entry.body),
+            lookup => panic!("unexpected lookup {:?}", lookup),
+        }
+
+        let url = "https://api.crossref.org/funders/100000015";
+        let route = "/funders/100000015";
+        cache.store(url, &TransportResponse::ok("{}")).unwrap();
+        assert!(matches!(
+            cache.lookup(url, route).unwrap(),
+            Lookup::Fresh(_)
+        ));
+        assert!(matches!(
+            cache.clone().bypass(true).lookup(url, route).unwrap(),
+            Lookup::Miss
+        ));
+    }
+
+    #[test]
+    fn memory_is_bounded() {
+        let cache = Cache::in_memory().max_entries(2);
+        let entry = |url: &str, stored_at| CacheEntry {
+            url: url.to_string(),
+            body: "{}".to_string(),
+            etag: None,
+            last_modified: None,
+            stored_at,
+        };
+        cache
+            .save(entry("https://api.crossref.org/types/a", 1))
+            .unwrap();
+        cache
+            .save(entry("https://api.crossref.org/types/b", 2))
+            .unwrap();
+        // replacing a cached response does not evict another one
+        cache
+            .save(entry("https://api.crossref.org/types/b", 3))
+            .unwrap();
+        assert_eq!(2, cache.lock().len());
+
+        cache
+            .store(
+                "https://api.crossref.org/types/c",
+                &TransportResponse::ok("{}"),
+            )
+            .unwrap();
+        let entries = cache.lock();
+        assert_eq!(2, entries.len());
+        assert!(!entries.contains_key("https://api.crossref.org/types/a"));
+        assert!(entries.contains_key("https://api.crossref.org/types/b"));
+    }
+
+    #[test]
+    fn on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://api.crossref.org/works/10.5555/1";
+        let mut resp = TransportResponse::ok("{}");
+        resp.headers
+            .insert(ETAG, HeaderValue::from_static("\"abc\""));
+        Cache::on_disk(dir.path()).store(url, &resp).unwrap();
+
+        // a new cache reads the responses of the previous one
+        let entry = match Cache::on_disk(dir.path())
+            .ttl(Duration::from_secs(0))
+            .lookup(url, "/works/10.5555/1")
+        {
+            Ok(Lookup::Stale(entry)) => entry,
+            lookup => panic!("unexpected lookup {:?}", lookup),
+        };
+        let mut headers = HeaderMap::new();
+        entry.conditional_headers(&mut headers);
+        assert_eq!(
+            Some(&HeaderValue::from_static("\"abc\"")),
+            headers.get(IF_NONE_MATCH)
+        );
+    }
+}
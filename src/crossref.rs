@@ -55,12 +55,8 @@ enum App {
     },
     #[structopt(name = "journals", about = "Query crossref journals")]
     Journals {
-        #[structopt(long = "id", help = "The id of component.")]
-        id: String,
-        #[structopt(flatten)]
-        client_opts: ClientOpts,
         #[structopt(flatten)]
-        out: Out,
+        opts: Opts,
     },
     #[structopt(name = "prefixes", about = "Query crossref prefixes")]
     Prefixes {
@@ -85,23 +81,23 @@ enum App {
 impl App {
     pub fn client_opts(&self) -> &ClientOpts {
         match self {
-            App::Works { opts, .. } | App::Funders { opts, .. } | App::Members { opts, .. } => {
-                &opts.client_opts
-            }
+            App::Works { opts, .. }
+            | App::Funders { opts, .. }
+            | App::Members { opts, .. }
+            | App::Journals { opts, .. } => &opts.client_opts,
 
-            App::Prefixes { client_opts, .. }
-            | App::Types { client_opts, .. }
-            | App::Journals { client_opts, .. } => client_opts,
+            App::Prefixes { client_opts, .. } | App::Types { client_opts, .. } => client_opts,
         }
     }
 
     pub fn out(&self) -> &Out {
         match self {
-            App::Works { opts, .. } | App::Funders { opts, .. } | App::Members { opts, .. } => {
-                &opts.out
-            }
+            App::Works { opts, .. }
+            | App::Funders { opts, .. }
+            | App::Members { opts, .. }
+            | App::Journals { opts, .. } => &opts.out,
 
-            App::Prefixes { out, .. } | App::Types { out, .. } | App::Journals { out, .. } => out,
+            App::Prefixes { out, .. } | App::Types { out, .. } => out,
         }
     }
 
@@ -121,10 +117,21 @@ impl App {
                 writer,
                 &client.prefix(id.as_str())?,
             )?),
-            App::Journals { id, .. } => Ok(serde_json::to_writer_pretty(
-                writer,
-                &client.journal(id.as_str())?,
-            )?),
+            App::Journals { opts, .. } => {
+                if let Some(id) = &opts.id {
+                    Ok(serde_json::to_writer_pretty(
+                        writer,
+                        &client.journal(id.as_str())?,
+                    )?)
+                } else {
+                    let mut query = JournalsQuery::default();
+                    query!(query, opts);
+                    Ok(serde_json::to_writer_pretty(
+                        writer,
+                        &client.journals(query)?,
+                    )?)
+                }
+            }
             App::Members { opts, .. } => {
                 if let Some(id) = &opts.id {
                     Ok(serde_json::to_writer_pretty(
@@ -213,7 +220,10 @@ impl App {
                         if *deep_page {
                             Ok(serde_json::to_writer_pretty(
                                 writer,
-                                &client.deep_page(query).into_work_iter().collect::<Vec<_>>(),
+                                &client
+                                    .deep_page(query)
+                                    .into_work_iter()
+                                    .collect::<Result<Vec<_>, _>>()?,
                             )?)
                         } else {
                             Ok(serde_json::to_writer_pretty(writer, &client.works(query)?)?)
@@ -1,4 +1,5 @@
-use crate::query::ResourceComponent;
+use crate::query::facet::Facet;
+use crate::query::{Component, ResourceComponent};
 use crate::response::MessageType;
 use failure::{Backtrace, Compat, Context, Fail};
 use serde::{de, ser};
@@ -13,6 +14,13 @@ pub struct Error {
     ctx: Context<ErrorKind>,
 }
 
+impl Error {
+    /// the kind of this error
+    pub fn kind(&self) -> &ErrorKind {
+        self.ctx.get_context()
+    }
+}
+
 impl Fail for Error {
     fn cause(&self) -> Option<&dyn Fail> {
         self.ctx.cause()
@@ -34,7 +42,10 @@ impl fmt::Display for Error {
 pub enum ErrorKind {
     /// if an invalid type was requested
     #[fail(display = "invalid type name: {}", name)]
-    InvalidTypeName { name: String },
+    InvalidTypeName {
+        /// the requested name
+        name: String,
+    },
 
     /// if there is a mismatch between the expected return type of the crossref api and this rust client
     #[fail(
@@ -42,7 +53,9 @@ pub enum ErrorKind {
         expected, got
     )]
     UnexpectedItem {
+        /// the type the client expected
         expected: MessageType,
+        /// the type crossref returned
         got: MessageType,
     },
     /// a config error
@@ -58,18 +71,190 @@ pub enum ErrorKind {
         /// the notification
         reqwest: reqwest::Error,
     },
+    /// when the server still answered with a retried status after all attempts were used
+    #[fail(
+        display = "request failed with status {} after {} attempts",
+        status, attempts
+    )]
+    RetriesExhausted {
+        /// the status of the last response
+        status: u16,
+        /// how many requests were made
+        attempts: usize,
+    },
     /// When no message was found but expected
     #[fail(
         display = "No message found but expected message of type `{}`",
         expected
     )]
-    MissingMessage { expected: MessageType },
+    MissingMessage {
+        /// the type the client expected
+        expected: MessageType,
+    },
     /// When crossref could not find anything
     #[fail(display = "Nothing was found for resource `{}`", resource)]
-    ResourceNotFound { resource: Box<ResourceComponent> },
+    ResourceNotFound {
+        /// the requested resource
+        resource: Box<ResourceComponent>,
+    },
     /// if a error in serde occurred
     #[fail(display = "invalid serde: {}", error)]
-    Serde { error: serde_json::Error },
+    Serde {
+        /// the error of serde
+        error: serde_json::Error,
+    },
+    /// when replaying fixtures and no fixture was recorded for a request
+    #[fail(display = "no fixture for `{}` at {}", url, path)]
+    MissingFixture {
+        /// the url of the request
+        url: String,
+        /// the file the fixture was expected in
+        path: String,
+    },
+    /// if an error occurred while reading or writing files
+    #[fail(display = "io error: {}", error)]
+    Io {
+        /// the io error
+        error: std::io::Error,
+    },
+    /// when the task of a request was cancelled before it finished, like when its runtime shut down
+    #[fail(display = "request cancelled: {}", msg)]
+    Cancelled {
+        /// why the task was cancelled
+        msg: String,
+    },
+    /// when requesting a page failed while deep paging
+    #[fail(display = "deep paging failed at page {}: {}", page, error)]
+    DeepPage {
+        /// the number of the page that failed, starting at 1
+        page: usize,
+        /// the cursor token the failed request was made with, if deep paging with a cursor
+        cursor: Option<String>,
+        /// the error of the failed request
+        error: Box<Error>,
+    },
+    /// when a record of a snapshot is not a valid `Work`
+    #[fail(display = "malformed record {} in {}: {}", record, file, msg)]
+    MalformedRecord {
+        /// the file of the snapshot the record is in
+        file: String,
+        /// the number of the record in the file, starting at 1
+        record: usize,
+        /// what is wrong with the record
+        msg: String,
+    },
+    /// when a url or route could not be parsed into a query
+    #[fail(display = "invalid route: {}", msg)]
+    InvalidRoute {
+        /// what is wrong with the route
+        msg: String,
+    },
+    /// when a route contains a filter the query does not support
+    #[fail(display = "unknown filter `{}`", name)]
+    UnknownFilter {
+        /// the name of the filter
+        name: String,
+    },
+    /// when a query breaks a rule of its route, checked before the request is made
+    #[fail(display = "invalid query: {}", violation)]
+    InvalidQuery {
+        /// the rule the query breaks
+        violation: Violation,
+    },
+    /// when no filter restricts a query to the works with a value of a facet, like for `publisher-name`
+    #[fail(
+        display = "no filter selects the works by the values of facet `{}`",
+        facet
+    )]
+    UnfilterableFacet {
+        /// the facet without a filter
+        facet: Facet,
+    },
+    /// when no filter restricts a query to the works with a value of a facet, or the value has the wrong type
+    #[fail(
+        display = "no filter selects the works with `{}` of facet `{}`",
+        value, facet
+    )]
+    UnfilterableFacetValue {
+        /// the facet the value belongs to
+        facet: Facet,
+        /// the value
+        value: String,
+    },
+    /// if an error occurred in the sqlite database of a `Store`
+    #[cfg(feature = "store")]
+    #[fail(display = "store error: {}", error)]
+    Store {
+        /// the sqlite error
+        error: rusqlite::Error,
+    },
+}
+
+/// The rules of the crossref api a query can break
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// more rows per page were requested than crossref returns
+    TooManyRows {
+        /// the requested rows
+        rows: usize,
+    },
+    /// the offset is beyond the last item crossref allows to reach by offset
+    OffsetTooLarge {
+        /// the requested offset
+        offset: usize,
+    },
+    /// a larger sample was requested than crossref returns
+    SampleTooLarge {
+        /// the requested sample size
+        sample: usize,
+    },
+    /// a sample can not be deep paged with a cursor
+    SampleWithCursor,
+    /// the parameter is only supported on the `/works` routes
+    WorksOnly {
+        /// the name of the parameter, like `facet`
+        param: &'static str,
+        /// the component of the route the parameter was used on
+        component: Component,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::TooManyRows { rows } => write!(
+                f,
+                "{} rows requested but at most {} are allowed",
+                rows,
+                crate::query::MAX_ROWS
+            ),
+            Violation::OffsetTooLarge { offset } => write!(
+                f,
+                "offset {} is beyond the maximum of {}, use a cursor instead",
+                offset,
+                crate::query::MAX_OFFSET
+            ),
+            Violation::SampleTooLarge { sample } => write!(
+                f,
+                "sample of {} requested but at most {} are allowed",
+                sample,
+                crate::query::MAX_SAMPLE
+            ),
+            Violation::SampleWithCursor => write!(f, "a sample can not be combined with a cursor"),
+            Violation::WorksOnly { param, component } => write!(
+                f,
+                "`{}` is only supported on the `/works` routes, not on `/{}`",
+                param,
+                component.as_str()
+            ),
+        }
+    }
+}
+
+impl From<Violation> for Error {
+    fn from(violation: Violation) -> Error {
+        ErrorKind::InvalidQuery { violation }.into()
+    }
 }
 
 impl From<ErrorKind> for Error {
@@ -90,6 +275,19 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        ErrorKind::Io { error }.into()
+    }
+}
+
+#[cfg(feature = "store")]
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Error {
+        ErrorKind::Store { error }.into()
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(reqwest: reqwest::Error) -> Error {
         ErrorKind::ReqWest { reqwest }.into()
@@ -0,0 +1,542 @@
+use crate::asynchronous::AsyncCrossref;
+use crate::error::{Error, ErrorKind, Result};
+use crate::query::ResultControl;
+use crate::response::Work;
+use crate::{WorkListQuery, WorkResultControl, WorksFilter};
+use chrono::{Duration, NaiveDate};
+use futures::stream::{self, BoxStream, SelectAll, Stream, StreamExt, TryStreamExt};
+use std::collections::{HashSet, VecDeque};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The date field the windows of a `Harvest` are applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionField {
+    /// the date the work was indexed, `from-index-date` and `until-index-date`
+    IndexDate,
+    /// the date the work was published, `from-pub-date` and `until-pub-date`
+    PubDate,
+}
+
+impl PartitionField {
+    /// the filters that restrict a query to the `window`
+    fn filters(self, window: DateWindow) -> [WorksFilter; 2] {
+        match self {
+            PartitionField::IndexDate => [
+                WorksFilter::FromIndexDate(window.from),
+                WorksFilter::UntilIndexDate(window.until),
+            ],
+            PartitionField::PubDate => [
+                WorksFilter::FromPubDate(window.from),
+                WorksFilter::UntilPubDate(window.until),
+            ],
+        }
+    }
+}
+
+/// A range of days, both ends inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateWindow {
+    /// the first day of the window
+    pub from: NaiveDate,
+    /// the last day of the window
+    pub until: NaiveDate,
+}
+
+impl DateWindow {
+    /// create a new window from `from` until `until`, both inclusive
+    pub fn new(from: NaiveDate, until: NaiveDate) -> Self {
+        DateWindow { from, until }
+    }
+
+    /// the number of days in this window
+    pub fn days(&self) -> i64 {
+        (self.until - self.from).num_days() + 1
+    }
+
+    /// splits this window into two halves, `None` if it is a single day
+    pub fn split(&self) -> Option<(DateWindow, DateWindow)> {
+        if self.days() < 2 {
+            return None;
+        }
+        let mid = self.from + Duration::days(self.days() / 2 - 1);
+        Some((
+            DateWindow::new(self.from, mid),
+            DateWindow::new(mid + Duration::days(1), self.until),
+        ))
+    }
+
+    /// splits this window into `parts` windows of about equal size
+    fn partition(&self, parts: usize) -> Vec<DateWindow> {
+        let parts = (parts.max(1) as i64).min(self.days().max(1));
+        let len = self.days() / parts;
+        let mut rest = self.days() % parts;
+        let mut from = self.from;
+        let mut windows = Vec::with_capacity(parts as usize);
+        for _ in 0..parts {
+            let mut days = len;
+            if rest > 0 {
+                days += 1;
+                rest -= 1;
+            }
+            let until = from + Duration::days(days - 1);
+            windows.push(DateWindow::new(from, until));
+            from = until + Duration::days(1);
+        }
+        windows
+    }
+}
+
+/// Harvests all works of a query by deep paging disjoint date windows at the same time
+///
+/// The date range is split into windows, each window is restricted with the `From`/`Until` filters of the
+/// `PartitionField` and deep paged with its own cursor. A window with more than `max_window_results` works
+/// is split in halves before it is paged, down to single days. Each window is paged with a new cursor,
+/// a result control of the query only sets the rows per page.
+/// All requests share the rate limit of the client. Works are deduplicated by their DOI,
+/// since a work that is indexed again during the harvest can show up in two windows.
+///
+/// The deduplication keeps the DOI of every returned work in memory until the harvest ends, about 50 bytes
+/// per work, so several gigabytes for a harvest of all of crossref. Turn it off with [`Harvest::dedupe`]
+/// if the consumer deduplicates anyway, like a store that upserts works by DOI.
+///
+/// # Example
+///
+/// ```edition2018
+/// # #[cfg(feature = "blocking")] {
+/// use crossref::{Crossref, Harvest, PartitionField, WorksQuery};
+/// use chrono::NaiveDate;
+/// # fn run() -> Result<(), crossref::Error> {
+/// let client = Crossref::builder().build()?;
+///
+/// let harvest = Harvest::new(
+///     WorksQuery::new("ontologies"),
+///     NaiveDate::from_ymd_opt(2018, 1, 1).unwrap(),
+///     NaiveDate::from_ymd_opt(2018, 12, 31).unwrap(),
+/// )
+/// .partition_by(PartitionField::PubDate)
+/// .concurrency(4);
+///
+/// for work in client.harvest(harvest) {
+///     println!("{}", work?.doi);
+/// }
+/// # Ok(())
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Harvest {
+    /// the query that is harvested
+    pub query: WorkListQuery,
+    /// the dates the windows cover
+    pub window: DateWindow,
+    /// the date field the windows apply to
+    pub field: PartitionField,
+    /// how many windows are paged at the same time
+    pub concurrency: usize,
+    /// windows with more results are split before they are paged
+    pub max_window_results: usize,
+    /// whether works that showed up in an earlier window are skipped
+    pub dedupe: bool,
+}
+
+impl Harvest {
+    /// the default upper bound for the results of a single window
+    pub const DEFAULT_MAX_WINDOW_RESULTS: usize = 100_000;
+
+    /// create a new harvest of all works of the `query` from `from` until `until`, both inclusive
+    pub fn new<T: Into<WorkListQuery>>(query: T, from: NaiveDate, until: NaiveDate) -> Self {
+        Harvest {
+            query: query.into(),
+            window: DateWindow::new(from, until),
+            field: PartitionField::IndexDate,
+            concurrency: 4,
+            max_window_results: Harvest::DEFAULT_MAX_WINDOW_RESULTS,
+            dedupe: true,
+        }
+    }
+
+    /// set the date field the windows apply to, `PartitionField::IndexDate` by default
+    pub fn partition_by(mut self, field: PartitionField) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// set how many windows are paged at the same time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// set the number of results above which a window is split
+    pub fn max_window_results(mut self, max_window_results: usize) -> Self {
+        self.max_window_results = max_window_results;
+        self
+    }
+
+    /// set whether works that showed up in an earlier window are skipped, `true` by default
+    ///
+    /// Without deduplication no DOIs are kept in memory, but a work can be returned twice.
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// the query restricted to the `window`, deep paged from its first page
+    fn window_query(&self, window: DateWindow) -> WorkListQuery {
+        let mut query = self.query.clone();
+        let works = query.query_mut();
+        works
+            .filter
+            .extend(self.field.filters(window).iter().cloned());
+        // rows or offsets would end the window after its first page, a cursor token belongs to another query
+        let rows = match &works.result_control {
+            Some(WorkResultControl::Cursor { rows, .. }) => *rows,
+            Some(WorkResultControl::Standard(ResultControl::Rows(rows)))
+            | Some(WorkResultControl::Standard(ResultControl::RowsOffset { rows, .. })) => {
+                Some(*rows)
+            }
+            _ => None,
+        };
+        works.result_control = Some(WorkResultControl::Cursor { token: None, rows });
+        query
+    }
+
+    /// checks that the window of this harvest is not empty
+    fn validate(&self) -> Result<()> {
+        if self.window.from > self.window.until {
+            return Err(ErrorKind::Config {
+                msg: format!(
+                    "the harvest starts at {} after it ends at {}",
+                    self.window.from, self.window.until
+                ),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// what a window yields
+enum WindowEvent {
+    /// the window was too large and has to be paged as two windows instead
+    Split(DateWindow, DateWindow),
+    /// a work of the window or the error that ended it
+    Work(Box<Result<Work>>),
+}
+
+/// A stream over the deduplicated works of a `Harvest`
+///
+/// Created by [`AsyncCrossref::harvest`]. The stream has to be polled from within a tokio runtime.
+/// An error ends only the window it occurred in, the other windows continue.
+/// A harvest that starts after it ends yields a single `Config` error.
+pub struct HarvestStream {
+    /// the harvest configuration
+    harvest: Harvest,
+    /// performs each request
+    client: AsyncCrossref,
+    /// the windows that were not started yet
+    pending: VecDeque<DateWindow>,
+    /// the windows that are paged right now
+    running: SelectAll<BoxStream<'static, WindowEvent>>,
+    /// the DOIs of all works that were returned, if deduplicating
+    seen: HashSet<String>,
+    /// the number of works that were returned
+    returned: usize,
+    /// why the harvest was rejected, returned before anything else
+    invalid: Option<Error>,
+}
+
+impl HarvestStream {
+    pub(crate) fn new(client: AsyncCrossref, harvest: Harvest) -> Self {
+        let (pending, invalid) = match harvest.validate() {
+            Ok(()) => (harvest.window.partition(harvest.concurrency).into(), None),
+            Err(err) => (VecDeque::new(), Some(err)),
+        };
+        HarvestStream {
+            pending,
+            running: SelectAll::new(),
+            seen: HashSet::new(),
+            returned: 0,
+            invalid,
+            harvest,
+            client,
+        }
+    }
+
+    /// the number of works returned so far, each counted once if deduplicating
+    pub fn works_seen(&self) -> usize {
+        self.returned
+    }
+
+    /// counts the works of the `window` first, then either splits it or deep pages it
+    fn start(&self, window: DateWindow) -> BoxStream<'static, WindowEvent> {
+        let client = self.client.clone();
+        let query = self.harvest.window_query(window);
+        let max_window_results = self.harvest.max_window_results;
+
+        let mut count = query.clone();
+        count.query_mut().result_control =
+            Some(WorkResultControl::Standard(ResultControl::Rows(0)));
+        let total = {
+            let client = client.clone();
+            async move { client.works(count).await.map(|list| list.total_results) }
+        };
+
+        stream::once(total)
+            .flat_map(move |total| match total {
+                Ok(total) if total > max_window_results => match window.split() {
+                    Some((first, second)) => {
+                        stream::once(async move { WindowEvent::Split(first, second) }).boxed()
+                    }
+                    None => pages(&client, query.clone()),
+                },
+                Ok(0) => stream::empty().boxed(),
+                Ok(_) => pages(&client, query.clone()),
+                Err(err) => {
+                    stream::once(async move { WindowEvent::Work(Box::new(Err(err))) }).boxed()
+                }
+            })
+            .boxed()
+    }
+}
+
+/// the works of all pages of the `query`
+fn pages(client: &AsyncCrossref, query: WorkListQuery) -> BoxStream<'static, WindowEvent> {
+    client
+        .deep_page(query)
+        .into_work_stream()
+        .into_stream()
+        .map(|work| WindowEvent::Work(Box::new(work)))
+        .boxed()
+}
+
+impl Stream for HarvestStream {
+    type Item = Result<Work>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(err) = this.invalid.take() {
+            return Poll::Ready(Some(Err(err)));
+        }
+        loop {
+            while this.running.len() < this.harvest.concurrency {
+                match this.pending.pop_front() {
+                    Some(window) => {
+                        let window = this.start(window);
+                        this.running.push(window);
+                    }
+                    None => break,
+                }
+            }
+
+            match this.running.poll_next_unpin(cx) {
+                Poll::Ready(Some(WindowEvent::Split(first, second))) => {
+                    // page the halves next, before windows that were not started yet
+                    this.pending.push_front(second);
+                    this.pending.push_front(first);
+                }
+                Poll::Ready(Some(WindowEvent::Work(work))) => match *work {
+                    Ok(work) => {
+                        if !this.harvest.dedupe || this.seen.insert(work.doi.to_lowercase()) {
+                            this.returned += 1;
+                            return Poll::Ready(Some(Ok(work)));
+                        }
+                    }
+                    Err(err) => return Poll::Ready(Some(Err(err))),
+                },
+                Poll::Ready(None) => {
+                    if this.pending.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::tests::work_list_response;
+    use crate::{CrossrefRoute, InMemoryTransport, WorksQuery};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn split_windows() {
+        let window = DateWindow::new(date(2019, 1, 1), date(2019, 1, 10));
+        assert_eq!(10, window.days());
+        assert_eq!(
+            Some((
+                DateWindow::new(date(2019, 1, 1), date(2019, 1, 5)),
+                DateWindow::new(date(2019, 1, 6), date(2019, 1, 10))
+            )),
+            window.split()
+        );
+        let (first, second) = DateWindow::new(date(2019, 1, 1), date(2019, 1, 2))
+            .split()
+            .unwrap();
+        assert_eq!((1, 1), (first.days(), second.days()));
+        assert_eq!(None, first.split());
+    }
+
+    #[test]
+    fn partition_windows() {
+        let window = DateWindow::new(date(2019, 1, 1), date(2019, 1, 10));
+        let windows = window.partition(3);
+        assert_eq!(
+            vec![
+                DateWindow::new(date(2019, 1, 1), date(2019, 1, 4)),
+                DateWindow::new(date(2019, 1, 5), date(2019, 1, 7)),
+                DateWindow::new(date(2019, 1, 8), date(2019, 1, 10)),
+            ],
+            windows
+        );
+        assert_eq!(10, window.partition(50).len());
+    }
+
+    /// serves the count and the pages of the window from `from` until `until` in january 2019
+    fn serve_window(
+        transport: &InMemoryTransport,
+        harvest: &Harvest,
+        (from, until): (u32, u32),
+        dois: &[&str],
+    ) {
+        let query =
+            harvest.window_query(DateWindow::new(date(2019, 1, from), date(2019, 1, until)));
+        let mut count = query.clone();
+        count.query_mut().result_control =
+            Some(WorkResultControl::Standard(ResultControl::Rows(0)));
+        transport.insert(
+            count.route().unwrap(),
+            crate::TransportResponse::ok(work_list_response(dois, None)),
+        );
+
+        let mut first = query.clone();
+        first.query_mut().result_control = Some(WorkResultControl::new_cursor());
+        let mut last = query;
+        last.query_mut().result_control = Some(WorkResultControl::cursor("next"));
+        transport.insert(
+            first.route().unwrap(),
+            crate::TransportResponse::ok(work_list_response(dois, Some("next"))),
+        );
+        transport.insert(
+            last.route().unwrap(),
+            crate::TransportResponse::ok(work_list_response(&[], Some("next"))),
+        );
+    }
+
+    #[tokio::test]
+    async fn splits_large_windows_and_dedupes() {
+        let harvest = Harvest::new(
+            WorksQuery::new("ontologies"),
+            date(2019, 1, 1),
+            date(2019, 1, 2),
+        )
+        .concurrency(1)
+        .max_window_results(2);
+
+        let transport = InMemoryTransport::new();
+        serve_window(
+            &transport,
+            &harvest,
+            (1, 2),
+            &["10.5555/1", "10.5555/2", "10.5555/3"],
+        );
+        serve_window(&transport, &harvest, (1, 1), &["10.5555/1", "10.5555/2"]);
+        // a work that was indexed again during the harvest shows up in both windows
+        serve_window(&transport, &harvest, (2, 2), &["10.5555/2", "10.5555/3"]);
+
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(transport.clone())
+            .build_async()
+            .unwrap();
+        let mut dois: Vec<_> = client
+            .harvest(harvest.clone())
+            .map_ok(|work| work.doi)
+            .try_collect()
+            .await
+            .unwrap();
+        dois.sort();
+        assert_eq!(vec!["10.5555/1", "10.5555/2", "10.5555/3"], dois);
+        // one count for the large window, a count and two pages for each half
+        assert_eq!(7, transport.requests().len());
+
+        let mut works = client.harvest(harvest.dedupe(false));
+        while works.try_next().await.unwrap().is_some() {}
+        assert_eq!(4, works.works_seen());
+    }
+
+    #[test]
+    fn windows_are_deep_paged() {
+        let window = DateWindow::new(date(2019, 1, 1), date(2019, 1, 1));
+        for (control, expected) in [
+            (
+                WorkResultControl::Standard(ResultControl::Rows(50)),
+                Some(50),
+            ),
+            (WorkResultControl::Standard(ResultControl::Offset(20)), None),
+            (WorkResultControl::cursor("abc"), None),
+        ] {
+            let harvest = Harvest::new(
+                WorksQuery::new("ontologies").result_control(control),
+                date(2019, 1, 1),
+                date(2019, 1, 31),
+            );
+            match harvest.window_query(window).query().result_control {
+                Some(WorkResultControl::Cursor { token: None, rows }) => assert_eq!(expected, rows),
+                ref control => panic!("unexpected result control {:?}", control),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_reversed_window() {
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(InMemoryTransport::new())
+            .build_async()
+            .unwrap();
+        let works: Vec<_> = client
+            .harvest(Harvest::new(
+                WorksQuery::new("ontologies"),
+                date(2019, 1, 2),
+                date(2019, 1, 1),
+            ))
+            .collect()
+            .await;
+        assert_eq!(1, works.len());
+        match &works[0] {
+            Err(err) => assert!(matches!(err.kind(), ErrorKind::Config { .. })),
+            Ok(work) => panic!("unexpected work {}", work.doi),
+        }
+    }
+
+    #[tokio::test]
+    async fn errors_end_only_their_window() {
+        let harvest = Harvest::new(
+            WorksQuery::new("ontologies"),
+            date(2019, 1, 1),
+            date(2019, 1, 2),
+        )
+        .concurrency(2);
+
+        // the first window is unknown to the transport and fails
+        let transport = InMemoryTransport::new();
+        serve_window(&transport, &harvest, (2, 2), &["10.5555/2"]);
+
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(transport)
+            .build_async()
+            .unwrap();
+        let works: Vec<_> = client.harvest(harvest).collect().await;
+        assert_eq!(2, works.len());
+        assert_eq!(1, works.iter().filter(|work| work.is_err()).count());
+    }
+}
@@ -1,8 +1,15 @@
 use crate::query::{CrossrefQueryParam, ParamFragment};
+use serde::de::{value, IntoDeserializer};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
 
 /// all available facets that can be set as filter in a query
-#[derive(Debug, Clone)]
+///
+/// Serializes as the name of the facet in the query string, like `"type-name"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Facet {
     /// Author affiliation
     Affiliation,
@@ -11,6 +18,7 @@ pub enum Facet {
     /// Funder DOI
     FunderDoi,
     /// Contributor ORCID
+    #[serde(rename = "orcid")]
     ORCID,
     /// Work container title, such as journal title, or book title
     ContainerTitle,
@@ -21,6 +29,7 @@ pub enum Facet {
     /// Significant update type
     UpdateType,
     /// Journal ISSN (any - print, electronic, link)
+    #[serde(rename = "issn")]
     ISSN,
     /// Earliest year of publication
     Published,
@@ -51,8 +60,8 @@ impl Facet {
         }
     }
 
-    /// the key name for the query fragment
-    fn as_str(&self) -> &str {
+    /// the name of the facet in the query string and in the facets of a response
+    pub fn as_str(&self) -> &str {
         match self {
             Facet::Affiliation => "affiliation",
             Facet::FunderName => "funder-name",
@@ -74,8 +83,26 @@ impl Facet {
     }
 }
 
-#[derive(Debug, Clone)]
-#[allow(missing_docs)]
+impl fmt::Display for Facet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Facet {
+    type Err = String;
+
+    /// parses the name of the facet, like `type-name`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Facet::deserialize(s.into_deserializer())
+            .map_err(|_: value::Error| format!("Unable to convert {} to Facet", s))
+    }
+}
+
+/// A facet and the maximum number of values crossref returns for it
+///
+/// Serializes like `{"facet": "type-name", "count": 10}`, the maximum is requested if the `count` is missing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FacetCount {
     /// the targeted facet
     pub facet: Facet,
@@ -102,20 +129,20 @@ impl FacetCount {
 }
 
 impl ParamFragment for FacetCount {
-    fn key(&self) -> Cow<str> {
+    fn key(&self) -> Cow<'_, str> {
         Cow::Borrowed(self.facet.as_str())
     }
-    fn value(&self) -> Option<Cow<str>> {
+    fn value(&self) -> Option<Cow<'_, str>> {
         Some(Cow::Owned(self.value()))
     }
 }
 
 impl CrossrefQueryParam for Vec<FacetCount> {
-    fn param_key(&self) -> Cow<str> {
+    fn param_key(&self) -> Cow<'_, str> {
         Cow::Borrowed("facet")
     }
 
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         Some(Cow::Owned(
             self.iter()
                 .map(ParamFragment::fragment)
@@ -1,11 +1,14 @@
-use crate::error::Result;
+use crate::error::{ErrorKind, Result};
 use crate::query::facet::FacetCount;
 use crate::query::works::{WorksCombiner, WorksFilter, WorksIdentQuery, WorksQuery};
 use crate::query::*;
 use std::borrow::Cow;
 
 /// filters supported for the /funders route
-#[derive(Debug, Clone)]
+///
+/// Serializes like the filters of a `WorksQuery`, e.g. `{"location": "Germany"}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum FundersFilter {
     /// funders located in specified country
     Location(String),
@@ -21,23 +24,36 @@ impl FundersFilter {
 }
 
 impl ParamFragment for FundersFilter {
-    fn key(&self) -> Cow<str> {
+    fn key(&self) -> Cow<'_, str> {
         Cow::Borrowed(self.name())
     }
 
-    fn value(&self) -> Option<Cow<str>> {
+    fn value(&self) -> Option<Cow<'_, str>> {
         match self {
             FundersFilter::Location(s) => Some(Cow::Borrowed(s.as_str())),
         }
     }
 }
 
-impl Filter for FundersFilter {}
+impl Filter for FundersFilter {
+    fn from_fragment(key: &str, value: Option<&str>) -> Result<Self> {
+        match key {
+            "location" => {
+                parse::filter_value(key, value).map(|loc| FundersFilter::Location(loc.to_string()))
+            }
+            name => Err(ErrorKind::UnknownFilter {
+                name: name.to_string(),
+            }
+            .into()),
+        }
+    }
+}
 
-impl_common_query!(FundersQuery, FundersFilter);
+impl_common_query!(FundersQuery, FundersFilter, Funders);
 
 /// constructs the request payload for the `/funders` route
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Funders {
     /// target a specific funder at `/funder/{id}`
     Identifier(String),
@@ -50,7 +66,11 @@ pub enum Funders {
 impl CrossrefRoute for Funders {
     fn route(&self) -> Result<String> {
         match self {
-            Funders::Identifier(s) => Ok(format!("{}/{}", Component::Funders.route()?, s)),
+            Funders::Identifier(s) => Ok(format!(
+                "{}/{}",
+                Component::Funders.route()?,
+                encode_ident(s)
+            )),
             Funders::Query(query) => {
                 let query = query.route()?;
                 if query.is_empty() {
@@ -68,4 +88,12 @@ impl CrossrefQuery for Funders {
     fn resource_component(self) -> ResourceComponent {
         ResourceComponent::Funders(self)
     }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Funders::Query(query) => query.validate(),
+            Funders::Works(combined) => combined.query.validate(),
+            _ => Ok(()),
+        }
+    }
 }
@@ -1,12 +1,45 @@
-use crate::error::Result;
+use crate::error::{ErrorKind, Result};
+use crate::query::facet::FacetCount;
 use crate::query::works::{WorksCombiner, WorksFilter, WorksIdentQuery, WorksQuery};
-use crate::query::{Component, CrossrefQuery, CrossrefRoute, ResourceComponent};
+use crate::query::*;
+use std::borrow::Cow;
+
+/// filters supported for the `/journals` route
+///
+/// Crossref supports no filters on the `/journals` route, so there are none and routes with filters fail to parse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JournalsFilter {}
+
+impl ParamFragment for JournalsFilter {
+    fn key(&self) -> Cow<'_, str> {
+        match *self {}
+    }
+
+    fn value(&self) -> Option<Cow<'_, str>> {
+        match *self {}
+    }
+}
+
+impl Filter for JournalsFilter {
+    fn from_fragment(key: &str, _value: Option<&str>) -> Result<Self> {
+        Err(ErrorKind::UnknownFilter {
+            name: key.to_string(),
+        }
+        .into())
+    }
+}
+
+impl_common_query!(JournalsQuery, JournalsFilter, Journals);
 
 /// constructs the request payload for the `/journals` route
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Journals {
     /// target a specific journal at `/journals/{id}`
     Identifier(String),
+    /// target all journals that match the query at `/journals?query...`
+    Query(JournalsQuery),
     /// target a `Work` for a specific funder at `/journals/{id}/works?query..`
     Works(WorksIdentQuery),
 }
@@ -14,7 +47,19 @@ pub enum Journals {
 impl CrossrefRoute for Journals {
     fn route(&self) -> Result<String> {
         match self {
-            Journals::Identifier(s) => Ok(format!("{}/{}", Component::Journals.route()?, s)),
+            Journals::Identifier(s) => Ok(format!(
+                "{}/{}",
+                Component::Journals.route()?,
+                encode_ident(s)
+            )),
+            Journals::Query(query) => {
+                let query = query.route()?;
+                if query.is_empty() {
+                    Component::Journals.route()
+                } else {
+                    Ok(format!("{}?{}", Component::Journals.route()?, query))
+                }
+            }
             Journals::Works(combined) => Self::combined_route(combined),
         }
     }
@@ -24,4 +69,12 @@ impl CrossrefQuery for Journals {
     fn resource_component(self) -> ResourceComponent {
         ResourceComponent::Journals(self)
     }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Journals::Query(query) => query.validate(),
+            Journals::Works(combined) => combined.query.validate(),
+            _ => Ok(()),
+        }
+    }
 }
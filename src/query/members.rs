@@ -1,10 +1,13 @@
-use crate::error::Result;
+use crate::error::{ErrorKind, Result};
 use crate::query::works::{WorksCombiner, WorksFilter, WorksIdentQuery, WorksQuery};
 use crate::query::*;
 use std::borrow::Cow;
 
 /// filters supported for the `/members` route
-#[derive(Debug, Clone)]
+///
+/// Serializes like the filters of a `WorksQuery`, e.g. `"has-public-references"` or `{"current-doi-count": 10}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum MembersFilter {
     /// Member has made their references public for one or more of their prefixes
     HasPublicReferences,
@@ -29,11 +32,11 @@ impl MembersFilter {
 }
 
 impl ParamFragment for MembersFilter {
-    fn key(&self) -> Cow<str> {
+    fn key(&self) -> Cow<'_, str> {
         Cow::Borrowed(self.name())
     }
 
-    fn value(&self) -> Option<Cow<str>> {
+    fn value(&self) -> Option<Cow<'_, str>> {
         match self {
             MembersFilter::HasPublicReferences => None,
             MembersFilter::ReferenceVisibility(vis) => Some(Cow::Borrowed(vis.as_str())),
@@ -43,12 +46,34 @@ impl ParamFragment for MembersFilter {
     }
 }
 
-impl Filter for MembersFilter {}
+impl Filter for MembersFilter {
+    fn from_fragment(key: &str, value: Option<&str>) -> Result<Self> {
+        match key {
+            "has-public-references" => {
+                parse::expect_true(key, value).map(|_| MembersFilter::HasPublicReferences)
+            }
+            "reference-visibility" => parse::filter_value(key, value)
+                .and_then(|vis| parse::from_name("visibility", vis))
+                .map(MembersFilter::ReferenceVisibility),
+            "blackfile-doi-count" => {
+                parse::parse_filter_value(key, value).map(MembersFilter::BlackfileDoiCount)
+            }
+            "current-doi-count" => {
+                parse::parse_filter_value(key, value).map(MembersFilter::CurrentDoiCount)
+            }
+            name => Err(ErrorKind::UnknownFilter {
+                name: name.to_string(),
+            }
+            .into()),
+        }
+    }
+}
 
-impl_common_query!(MembersQuery, MembersFilter);
+impl_common_query!(MembersQuery, MembersFilter, Members);
 
 /// constructs the request payload for the `/members` route
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Members {
     /// target a specific member at `/members/{id}`
     Identifier(String),
@@ -61,7 +86,11 @@ pub enum Members {
 impl CrossrefRoute for Members {
     fn route(&self) -> Result<String> {
         match self {
-            Members::Identifier(s) => Ok(format!("{}/{}", Component::Members.route()?, s)),
+            Members::Identifier(s) => Ok(format!(
+                "{}/{}",
+                Component::Members.route()?,
+                encode_ident(s)
+            )),
             Members::Query(query) => {
                 let query = query.route()?;
                 if query.is_empty() {
@@ -79,4 +108,12 @@ impl CrossrefQuery for Members {
     fn resource_component(self) -> ResourceComponent {
         ResourceComponent::Members(self)
     }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Members::Query(query) => query.validate(),
+            Members::Works(combined) => combined.query.validate(),
+            _ => Ok(()),
+        }
+    }
 }
@@ -1,20 +1,23 @@
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, Violation};
 use crate::query::facet::FacetCount;
 pub use crate::query::funders::{Funders, FundersQuery};
-pub use crate::query::journals::Journals;
+pub use crate::query::journals::{Journals, JournalsQuery};
 pub use crate::query::members::{Members, MembersQuery};
 pub use crate::query::prefixes::Prefixes;
 pub use crate::query::types::{Type, Types};
-use crate::query::works::{Works, WorksFilter};
+use crate::query::works::{WorkListQuery, Works, WorksFilter};
 pub use crate::query::works::{WorksIdentQuery, WorksQuery};
 use chrono::NaiveDate;
 use core::fmt::Debug;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::fmt;
 use std::str::FromStr;
 #[cfg(feature = "cli")]
 use structopt::StructOpt;
+use url::Url;
 
 /// Helper trait for unified interface
 pub trait CrossrefParams {
@@ -35,9 +38,13 @@ pub trait CrossrefParams {
 }
 
 macro_rules! impl_common_query {
-    ($i:ident, $filter:ident) => {
+    ($i:ident, $filter:ident, $component:ident) => {
         /// Each query parameter is ANDed
-        #[derive(Debug, Clone, Default)]
+        ///
+        /// Serializes to a map of the fields in kebab-case, all of them optional,
+        /// like `{"queries": ["..."], "filter": [...], "result-control": {"rows": 20}}`.
+        #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+        #[serde(default, rename_all = "kebab-case")]
         pub struct $i {
             /// search by non specific query
             pub queries: Vec<String>,
@@ -110,6 +117,26 @@ macro_rules! impl_common_query {
                 self.result_control = Some(result_control);
                 self
             }
+
+            /// checks the query against the rules of its route, before any request is made
+            ///
+            /// # Errors
+            ///
+            /// Fails with `InvalidQuery` if the rows, offset or sample exceed the limits of crossref,
+            /// or if facets or a sample are used, which only `/works` supports
+            pub fn validate(&self) -> Result<()> {
+                if !self.facets.is_empty() {
+                    return Err(Violation::WorksOnly {
+                        param: "facet",
+                        component: Component::$component,
+                    }
+                    .into());
+                }
+                match &self.result_control {
+                    Some(rc) => rc.validate(Component::$component),
+                    None => Ok(()),
+                }
+            }
         }
 
         impl CrossrefParams for $i {
@@ -162,6 +189,33 @@ macro_rules! impl_common_query {
                 Ok(params.join("&"))
             }
         }
+
+        impl FromStr for $i {
+            type Err = Error;
+
+            /// parses an absolute url, a route or only the query string of the route
+            fn from_str(s: &str) -> Result<Self> {
+                Self::try_from(parse::parse_query_url(s, Component::$component)?)
+            }
+        }
+
+        impl TryFrom<Url> for $i {
+            type Error = Error;
+
+            fn try_from(url: Url) -> Result<Self> {
+                parse::expect_component(&url, Component::$component)?;
+                let params = parse::Params::<$filter>::parse(&url)?;
+                params.expect_common()?;
+                Ok($i {
+                    result_control: params.result_control()?,
+                    queries: params.queries,
+                    filter: params.filter,
+                    sort: params.sort,
+                    order: params.order,
+                    facets: params.facets,
+                })
+            }
+        }
     };
 }
 
@@ -173,6 +227,8 @@ pub mod funders;
 pub mod journals;
 /// provides support to query the `/journals` route
 pub mod members;
+/// parses urls and routes of the crossref api into queries
+mod parse;
 /// provides support to query the `/members` route
 pub mod prefixes;
 /// provides support to query the `/prefixes` route
@@ -202,7 +258,10 @@ impl Visibility {
 }
 
 /// Determines how results should be sorted
-#[derive(Debug, PartialEq, Eq, Clone)]
+///
+/// Serializes as `"asc"` or `"desc"`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 #[cfg_attr(feature = "cli", derive(StructOpt))]
 pub enum Order {
     /// list results in ascending order
@@ -229,7 +288,6 @@ impl Order {
     }
 }
 
-#[cfg(feature = "cli")]
 impl FromStr for Order {
     type Err = String;
 
@@ -243,17 +301,20 @@ impl FromStr for Order {
 }
 
 impl CrossrefQueryParam for Order {
-    fn param_key(&self) -> Cow<str> {
+    fn param_key(&self) -> Cow<'_, str> {
         Cow::Borrowed("order")
     }
 
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         Some(Cow::Borrowed(self.as_str()))
     }
 }
 
 /// Results from a list response can be sorted by applying the sort and order parameters.
-#[derive(Debug, Clone)]
+///
+/// Serializes as the value of the `sort` parameter, like `"published"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 #[cfg_attr(feature = "cli", derive(StructOpt))]
 pub enum Sort {
     /// Sort by relevance score
@@ -342,13 +403,12 @@ impl Sort {
             Sort::PublishedPrint => "published-print",
             Sort::PublishedOnline => "published-online",
             Sort::Issued => "issued",
-            Sort::IsReferencedByCount => "is-reference-by-count",
+            Sort::IsReferencedByCount => "is-referenced-by-count",
             Sort::ReferenceCount => "reference-count",
         }
     }
 }
 
-#[cfg(feature = "cli")]
 impl FromStr for Sort {
     type Err = String;
 
@@ -362,7 +422,7 @@ impl FromStr for Sort {
             "published-print" => Ok(Sort::PublishedPrint),
             "published-online" => Ok(Sort::PublishedOnline),
             "issued" => Ok(Sort::Issued),
-            "is-reference-by-count" => Ok(Sort::IsReferencedByCount),
+            "is-referenced-by-count" | "is-reference-by-count" => Ok(Sort::IsReferencedByCount),
             "reference-count" => Ok(Sort::ReferenceCount),
             other => Err(format!("Unable to convert {} to Sort", other)),
         }
@@ -370,17 +430,29 @@ impl FromStr for Sort {
 }
 
 impl CrossrefQueryParam for Sort {
-    fn param_key(&self) -> Cow<str> {
+    fn param_key(&self) -> Cow<'_, str> {
         Cow::Borrowed("sort")
     }
 
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         Some(Cow::Borrowed(self.as_str()))
     }
 }
 
+/// the most rows crossref returns per page
+pub const MAX_ROWS: usize = 1000;
+
+/// the largest offset crossref accepts, deeper pages need a cursor
+pub const MAX_OFFSET: usize = 10_000;
+
+/// the largest sample crossref returns
+pub const MAX_SAMPLE: usize = 100;
+
 /// tells crossref how many items shall be returned or where to start
-#[derive(Debug, Clone)]
+///
+/// Serializes like `{"rows": 20}`, `{"offset": 40}`, `{"rows-offset": {"rows": 20, "offset": 40}}` or `{"sample": 10}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ResultControl {
     /// limits the returned items per page
     Rows(usize),
@@ -399,29 +471,85 @@ pub enum ResultControl {
 }
 
 impl CrossrefQueryParam for ResultControl {
-    fn param_key(&self) -> Cow<str> {
+    fn param_key(&self) -> Cow<'_, str> {
         match self {
             ResultControl::Rows(_) => Cow::Borrowed("rows"),
             ResultControl::Offset(_) => Cow::Borrowed("offset"),
-            ResultControl::RowsOffset { rows, .. } => Cow::Owned(format!("rows={}", rows)),
+            ResultControl::RowsOffset { .. } => Cow::Borrowed("rows"),
             ResultControl::Sample(_) => Cow::Borrowed("sample"),
         }
     }
 
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         match self {
             ResultControl::Rows(r) | ResultControl::Offset(r) | ResultControl::Sample(r) => {
                 Some(Cow::Owned(r.to_string()))
             }
-            ResultControl::RowsOffset { offset, .. } => {
-                Some(Cow::Owned(format!("offset={}", offset)))
+            // both are separate parameters in the query string
+            ResultControl::RowsOffset { rows, offset } => {
+                Some(Cow::Owned(format!("{}&offset={}", rows, offset)))
+            }
+        }
+    }
+}
+
+impl ResultControl {
+    /// checks the rows, offset and sample against the limits of crossref for a route of the `component`
+    ///
+    /// # Errors
+    ///
+    /// Fails with `InvalidQuery` if a limit is exceeded or a sample is used outside of `/works`
+    pub fn validate(&self, component: Component) -> Result<()> {
+        match *self {
+            ResultControl::Rows(rows) => validate_rows(rows),
+            ResultControl::Offset(offset) => validate_offset(offset),
+            ResultControl::RowsOffset { rows, offset } => {
+                validate_rows(rows)?;
+                validate_offset(offset)
+            }
+            ResultControl::Sample(sample) => {
+                if component != Component::Works {
+                    return Err(Violation::WorksOnly {
+                        param: "sample",
+                        component,
+                    }
+                    .into());
+                }
+                validate_sample(sample)
             }
         }
     }
 }
 
+/// fails if more than `MAX_ROWS` rows are requested
+pub(crate) fn validate_rows(rows: usize) -> Result<()> {
+    if rows > MAX_ROWS {
+        Err(Violation::TooManyRows { rows }.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// fails if the offset is beyond `MAX_OFFSET`
+fn validate_offset(offset: usize) -> Result<()> {
+    if offset > MAX_OFFSET {
+        Err(Violation::OffsetTooLarge { offset }.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// fails if a sample of more than `MAX_SAMPLE` works is requested
+pub(crate) fn validate_sample(sample: usize) -> Result<()> {
+    if sample > MAX_SAMPLE {
+        Err(Violation::SampleTooLarge { sample }.into())
+    } else {
+        Ok(())
+    }
+}
+
 /// Major resource components supported by the Crossref API
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Component {
     /// returns a list of all works (journal articles, conference proceedings, books, components, etc), 20 per page
@@ -459,7 +587,10 @@ impl CrossrefRoute for Component {
 }
 
 /// bundles all available crossref api endpoints
-#[derive(Debug, Clone)]
+///
+/// Serializes to a map with the component as only key, like `{"works": {"identifier": "10.5555/12345678"}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ResourceComponent {
     /// returns a list of all works (journal articles, conference proceedings, books, components, etc), 20 per page
     Works(Works),
@@ -508,23 +639,128 @@ impl CrossrefRoute for ResourceComponent {
     }
 }
 
+/// Parses any url or route of the crossref api
+///
+/// # Example
+///
+/// ```edition2018
+/// use crossref::query::ResourceComponent;
+/// use crossref::Members;
+///
+/// let component: ResourceComponent = "https://api.crossref.org/members/78/works?filter=has-orcid:true&query.author=smith&rows=20"
+///     .parse()
+///     .unwrap();
+/// match component {
+///     ResourceComponent::Members(Members::Works(ident)) => assert_eq!("78", ident.id),
+///     _ => unreachable!(),
+/// }
+/// ```
+impl FromStr for ResourceComponent {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(parse::parse_url(s)?)
+    }
+}
+
+impl TryFrom<Url> for ResourceComponent {
+    type Error = Error;
+
+    fn try_from(url: Url) -> Result<Self> {
+        let segments = parse::path_segments(&url)?;
+        let component: Component = match segments.first() {
+            Some(component) => parse::from_name("component", component)?,
+            None => return Err(parse::invalid("the route has no resource component")),
+        };
+        let ids = &segments[1..];
+        if component != Component::Works && ids.len() == 2 && ids[1] == "works" {
+            return Ok(WorkListQuery::from_url(&url)?.resource_component());
+        }
+        if !ids.is_empty() {
+            parse::expect_no_params(&url)?;
+        }
+        let id = ids.join("/");
+        let unsupported = || {
+            Err(parse::invalid(format!(
+                "unsupported route `{}`",
+                url.path()
+            )))
+        };
+        Ok(match component {
+            Component::Works => match ids {
+                [] => ResourceComponent::Works(Works::Query(WorksQuery::try_from(url)?)),
+                [.., agency] if ids.len() > 1 && agency == "agency" => {
+                    let doi = ids[..ids.len() - 1].join("/");
+                    ResourceComponent::Works(Works::Agency(doi))
+                }
+                _ => ResourceComponent::Works(Works::Identifier(id)),
+            },
+            Component::Funders => match ids {
+                [] => ResourceComponent::Funders(Funders::Query(FundersQuery::try_from(url)?)),
+                [_] => ResourceComponent::Funders(Funders::Identifier(id)),
+                _ => return unsupported(),
+            },
+            Component::Members => match ids {
+                [] => ResourceComponent::Members(Members::Query(MembersQuery::try_from(url)?)),
+                [_] => ResourceComponent::Members(Members::Identifier(id)),
+                _ => return unsupported(),
+            },
+            Component::Types => match ids {
+                [] => {
+                    parse::expect_no_params(&url)?;
+                    ResourceComponent::Types(Types::All)
+                }
+                [_] => ResourceComponent::Types(Types::Identifier(id)),
+                _ => return unsupported(),
+            },
+            Component::Prefixes => match ids {
+                [_] => ResourceComponent::Prefixes(Prefixes::Identifier(id)),
+                _ => return unsupported(),
+            },
+            Component::Journals => match ids {
+                [] => ResourceComponent::Journals(Journals::Query(JournalsQuery::try_from(url)?)),
+                [_] => ResourceComponent::Journals(Journals::Identifier(id)),
+                _ => return unsupported(),
+            },
+        })
+    }
+}
+
 impl CrossrefQuery for ResourceComponent {
     fn resource_component(self) -> ResourceComponent {
         self
     }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            ResourceComponent::Works(c) => c.validate(),
+            ResourceComponent::Funders(c) => c.validate(),
+            ResourceComponent::Prefixes(c) => c.validate(),
+            ResourceComponent::Members(c) => c.validate(),
+            ResourceComponent::Types(c) => c.validate(),
+            ResourceComponent::Journals(c) => c.validate(),
+        }
+    }
 }
 
 /// Helper trait to mark filters in the query string
-pub trait Filter: ParamFragment {}
+pub trait Filter: ParamFragment {
+    /// parses the filter from the decoded `key` and `value` of its fragment
+    ///
+    /// Fails with `ErrorKind::UnknownFilter` if there is no filter named `key`.
+    fn from_fragment(key: &str, value: Option<&str>) -> Result<Self>
+    where
+        Self: Sized;
+}
 
 impl<T: Filter> CrossrefQueryParam for Vec<T> {
     /// always use `filter` as the key
-    fn param_key(&self) -> Cow<str> {
+    fn param_key(&self) -> Cow<'_, str> {
         Cow::Borrowed("filter")
     }
 
     /// filters are multi value and values are concat with `,`
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         Some(Cow::Owned(
             self.iter()
                 .map(ParamFragment::fragment)
@@ -537,15 +773,15 @@ impl<T: Filter> CrossrefQueryParam for Vec<T> {
 /// represents a key value pair inside a multi value query string parameter
 pub trait ParamFragment {
     /// the key, or name, of the fragment
-    fn key(&self) -> Cow<str>;
+    fn key(&self) -> Cow<'_, str>;
 
     /// the value of the fragment, if any
-    fn value(&self) -> Option<Cow<str>>;
+    fn value(&self) -> Option<Cow<'_, str>>;
 
-    /// key and value are concat using `:`
-    fn fragment(&self) -> Cow<str> {
+    /// key and value are concat using `:`, the value is percent-encoded
+    fn fragment(&self) -> Cow<'_, str> {
         if let Some(val) = self.value() {
-            Cow::Owned(format!("{}:{}", self.key(), val))
+            Cow::Owned(format!("{}:{}", self.key(), encode_value(val)))
         } else {
             self.key()
         }
@@ -555,11 +791,11 @@ pub trait ParamFragment {
 /// a trait used to capture parameters for the query string of the crossref api
 pub trait CrossrefQueryParam {
     /// the key name of the parameter in the query string
-    fn param_key(&self) -> Cow<str>;
+    fn param_key(&self) -> Cow<'_, str>;
     /// the value of the parameter, if any
-    fn param_value(&self) -> Option<Cow<str>>;
+    fn param_value(&self) -> Option<Cow<'_, str>>;
     /// constructs the full parameter for the query string by combining the key and value
-    fn param(&self) -> Cow<str> {
+    fn param(&self) -> Cow<'_, str> {
         if let Some(val) = self.param_value() {
             Cow::Owned(format!("{}={}", self.param_key(), val))
         } else {
@@ -569,11 +805,11 @@ pub trait CrossrefQueryParam {
 }
 
 impl<T: AsRef<str>> CrossrefQueryParam for (T, T) {
-    fn param_key(&self) -> Cow<str> {
+    fn param_key(&self) -> Cow<'_, str> {
         Cow::Borrowed(self.0.as_ref())
     }
 
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         Some(Cow::Borrowed(self.1.as_ref()))
     }
 }
@@ -604,13 +840,53 @@ pub trait CrossrefQuery: CrossrefRoute + Clone {
     fn to_url(&self, base_path: &str) -> Result<String> {
         Ok(format!("{}{}", base_path, self.route()?))
     }
+
+    /// checks the query against the rules of its route, before any request is made
+    ///
+    /// The clients run this for every request, routes without parameters are always valid.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `InvalidQuery` if crossref would reject the query
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// the characters that are percent-encoded in an identifier of a route path
+///
+/// `/` is kept, since DOIs contain it and crossref expects it unencoded.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// percent-encodes a value for the query string, like a search term, filter value or cursor
+pub(crate) fn encode_value<T: AsRef<str>>(value: T) -> String {
+    url::form_urlencoded::byte_serialize(value.as_ref().as_bytes()).collect()
 }
 
-/// formats the topic for crossref by replacing all whitespaces whit `+`
+/// percent-encodes an identifier, like a DOI, for the path of a route
+pub(crate) fn encode_ident<T: AsRef<str>>(ident: T) -> String {
+    utf8_percent_encode(ident.as_ref(), PATH_SEGMENT).to_string()
+}
+
+/// formats the topic for crossref by percent-encoding each word and joining them with `+`
 pub(crate) fn format_query<T: AsRef<str>>(topic: T) -> String {
     topic
         .as_ref()
         .split_whitespace()
+        .map(encode_value)
         .collect::<Vec<_>>()
         .join("+")
 }
@@ -624,3 +900,387 @@ pub(crate) fn format_queries<T: AsRef<str>>(topics: &[T]) -> String {
         .collect::<Vec<_>>()
         .join("+")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::works::{FieldQuery, WorkResultControl};
+    use proptest::prelude::*;
+    use url::Url;
+
+    /// parses the route of the `query` as it is requested from crossref
+    fn parse<T: CrossrefRoute>(query: &T) -> Url {
+        Url::parse(&format!(
+            "https://api.crossref.org{}",
+            query.route().unwrap()
+        ))
+        .unwrap()
+    }
+
+    /// the decoded value of the `key` in the query string of the `url`
+    fn param(url: &Url, key: &str) -> Option<String> {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    /// the decoded path of the `url`
+    fn path(url: &Url) -> String {
+        url.path_segments()
+            .unwrap()
+            .map(|segment| {
+                percent_encoding::percent_decode_str(segment)
+                    .decode_utf8()
+                    .unwrap()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    #[test]
+    fn encode_reserved_chars() {
+        let query = WorksQuery::new("Fish & Chips: #1 in Zürich")
+            .filter(WorksFilter::ContainerTitle("Nature, Physics".to_string()));
+        assert_eq!(
+            "/works?query=Fish+%26+Chips%3A+%231+in+Z%C3%BCrich&filter=container-title:Nature%2C+Physics",
+            query.route().unwrap()
+        );
+        assert_eq!(
+            "/works/10.1002/(SICI)1097-4636(199706)35:4%3C431::AID-JBM4%3E3.0.CO;2-G",
+            Works::doi("10.1002/(SICI)1097-4636(199706)35:4<431::AID-JBM4>3.0.CO;2-G")
+                .route()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_resource_components() {
+        let component: ResourceComponent =
+            "https://api.crossref.org/members/78/works?filter=has-orcid:true&query.author=smith&rows=20"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            "/members/78/works?query.author=smith&filter=has-orcid:true&rows=20",
+            component.route().unwrap()
+        );
+        match component {
+            ResourceComponent::Members(Members::Works(ident)) => assert_eq!("78", ident.id),
+            other => panic!("unexpected component {:?}", other),
+        }
+
+        for route in &[
+            "/works?query=ontologies&rows=5&offset=10",
+            "/works/10.1002/(SICI)1097-4636(199706)35:4%3C431::AID-JBM4%3E3.0.CO;2-G",
+            "/works/10.1037/0003-066X.59.1.29/agency",
+            "/funders?query=nih&filter=location:United+States",
+            "/funders/100000015",
+            "/funders/100000015/works?filter=type:journal-article",
+            "/members?filter=has-public-references,current-doi-count:10&sort=score&order=asc",
+            "/members/98",
+            "/prefixes/10.1016",
+            "/prefixes/10.1016/works?sample=5",
+            "/journals?query=nature&rows=5",
+            "/journals/0028-0836",
+            "/types",
+            "/types/journal-article",
+        ] {
+            let component: ResourceComponent = route.parse().unwrap();
+            assert_eq!(*route, component.route().unwrap());
+        }
+
+        match "/works/10.5555/12345678?mailto=polite@example.com".parse() {
+            Ok(ResourceComponent::Works(Works::Identifier(doi))) => {
+                assert_eq!("10.5555/12345678", doi)
+            }
+            other => panic!("unexpected component {:?}", other),
+        }
+        assert!("/prefixes".parse::<ResourceComponent>().is_err());
+        assert!("/publishers/1".parse::<ResourceComponent>().is_err());
+        assert!("/members/98?rows=1".parse::<ResourceComponent>().is_err());
+    }
+
+    #[test]
+    fn parse_common_queries() {
+        let query: MembersQuery = "query=elsevier&facet=publisher-name:*&rows=5&offset=10"
+            .parse()
+            .unwrap();
+        assert_eq!(vec!["elsevier".to_string()], query.queries);
+        assert_eq!(
+            "query=elsevier&facet=publisher-name:*&rows=5&offset=10",
+            query.route().unwrap()
+        );
+        let query: FundersQuery =
+            "https://api.crossref.org/funders?filter=location:Germany&sample=3"
+                .parse()
+                .unwrap();
+        assert_eq!("filter=location:Germany&sample=3", query.route().unwrap());
+
+        match "/funders?filter=has-orcid:true"
+            .parse::<FundersQuery>()
+            .unwrap_err()
+            .kind()
+        {
+            crate::ErrorKind::UnknownFilter { name } => assert_eq!("has-orcid", name),
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!("/works?query=nih".parse::<FundersQuery>().is_err());
+        assert!("query.author=smith".parse::<MembersQuery>().is_err());
+        assert!("rows=5&sample=5".parse::<MembersQuery>().is_err());
+        assert!("rows=many".parse::<MembersQuery>().is_err());
+        match "/journals?filter=issn:0028-0836"
+            .parse::<JournalsQuery>()
+            .unwrap_err()
+            .kind()
+        {
+            crate::ErrorKind::UnknownFilter { name } => assert_eq!("issn", name),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn wire_format_regressions() {
+        use crate::query::works::WorksQuery;
+
+        // was sent as `is-reference-by-count`
+        assert_eq!(
+            "/works?sort=is-referenced-by-count",
+            WorksQuery::empty()
+                .sort(Sort::IsReferencedByCount)
+                .route()
+                .unwrap()
+        );
+        // was sent as `rows=5=offset=10`
+        assert_eq!(
+            "/members?rows=5&offset=10",
+            Members::Query(
+                MembersQuery::empty().result_control(ResultControl::RowsOffset {
+                    rows: 5,
+                    offset: 10,
+                })
+            )
+            .route()
+            .unwrap()
+        );
+        // was sent as `title=renear`
+        assert_eq!(
+            "/works?query.title=renear",
+            WorksQuery::empty()
+                .field_query(FieldQuery::title("renear"))
+                .route()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn validate_queries() {
+        use crate::error::ErrorKind;
+        use crate::query::works::WorksQuery;
+
+        let violation = |result: Result<()>| match result.unwrap_err().kind() {
+            ErrorKind::InvalidQuery { violation } => violation.clone(),
+            other => panic!("unexpected error {:?}", other),
+        };
+
+        assert!(WorksQuery::new("ontologies")
+            .result_control(WorkResultControl::Standard(ResultControl::RowsOffset {
+                rows: MAX_ROWS,
+                offset: MAX_OFFSET,
+            }))
+            .validate()
+            .is_ok());
+        assert_eq!(
+            Violation::TooManyRows { rows: 1001 },
+            violation(
+                WorksQuery::new("ontologies")
+                    .result_control(WorkResultControl::Cursor {
+                        token: None,
+                        rows: Some(1001),
+                    })
+                    .validate()
+            )
+        );
+        assert_eq!(
+            Violation::OffsetTooLarge { offset: 10_001 },
+            violation(
+                WorkListQuery::from(
+                    WorksQuery::new("ontologies")
+                        .result_control(WorkResultControl::Standard(ResultControl::Offset(10_001)))
+                )
+                .validate()
+            )
+        );
+        assert_eq!(
+            Violation::SampleTooLarge { sample: 101 },
+            violation(WorksQuery::random(101).validate())
+        );
+        assert_eq!(
+            Violation::SampleWithCursor,
+            violation(WorksQuery::random(10).new_cursor().validate())
+        );
+        assert_eq!(
+            Violation::WorksOnly {
+                param: "facet",
+                component: Component::Funders,
+            },
+            violation(
+                FundersQuery::new("nsf")
+                    .facet(FacetCount {
+                        facet: facet::Facet::ORCID,
+                        count: None,
+                    })
+                    .validate()
+            )
+        );
+        assert_eq!(
+            Violation::WorksOnly {
+                param: "sample",
+                component: Component::Members,
+            },
+            violation(
+                Members::Query(MembersQuery::empty().result_control(ResultControl::Sample(5)))
+                    .validate()
+            )
+        );
+        assert!(ResourceComponent::Members(Members::Works(
+            WorksQuery::random(500).into_ident("98")
+        ))
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn serialize_queries() {
+        let query = WorksQuery::new("ontologies")
+            .filter(WorksFilter::HasOrcid)
+            .filter(WorksFilter::LicenseUrl("http://example.com".to_string()))
+            .filter(WorksFilter::Type(Type::JournalArticle))
+            .result_control(WorkResultControl::Standard(ResultControl::RowsOffset {
+                rows: 5,
+                offset: 10,
+            }));
+        assert_eq!(
+            serde_json::json!({
+                "free-form-queries": ["ontologies"],
+                "field-queries": [],
+                "filter": [
+                    "has-orcid",
+                    {"license.url": "http://example.com"},
+                    {"type": "journal-article"}
+                ],
+                "sort": null,
+                "order": null,
+                "facets": [],
+                "result-control": {"standard": {"rows-offset": {"rows": 5, "offset": 10}}},
+                "sample": null,
+                "select": []
+            }),
+            serde_json::to_value(&query).unwrap()
+        );
+
+        let toml = toml::to_string(&query).unwrap();
+        let parsed: WorksQuery = toml::from_str(&toml).unwrap();
+        assert_eq!(query.route().unwrap(), parsed.route().unwrap());
+
+        let members: MembersQuery = toml::from_str(
+            r#"
+queries = ["elsevier"]
+filter = ["has-public-references", { reference-visibility = "open" }]
+facets = [{ facet = "publisher-name" }]
+sort = "score"
+result-control = { rows = 5 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            "query=elsevier&filter=has-public-references,reference-visibility:open&facet=publisher-name:*&sort=score&rows=5",
+            members.route().unwrap()
+        );
+
+        for route in &[
+            "/funders/100000015/works?query=ontologies&filter=until-pub-date:2020-12-31",
+            "/members?query=elsevier&sample=3",
+            "/works/10.1037/0003-066X.59.1.29/agency",
+            "/types",
+        ] {
+            let component: ResourceComponent = route.parse().unwrap();
+            let toml = toml::to_string(&component).unwrap();
+            let parsed: ResourceComponent = toml::from_str(&toml).unwrap();
+            assert_eq!(*route, parsed.route().unwrap());
+        }
+        assert_eq!(
+            serde_json::json!({"prefixes": {"identifier": "10.1016"}}),
+            serde_json::to_value(ResourceComponent::Prefixes(Prefixes::Identifier(
+                "10.1016".to_string()
+            )))
+            .unwrap()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn query_terms_round_trip(term in any::<String>()) {
+            let url = parse(&WorksQuery::new(&term));
+            let words = term.split_whitespace().collect::<Vec<_>>().join(" ");
+            prop_assert_eq!(Some(words), param(&url, "query"));
+        }
+
+        #[test]
+        fn field_query_round_trip(title in "\\PC*") {
+            let field_query = FieldQuery::title(&title);
+            // crossref expects field queries as `query.{name}`
+            let key = format!("query.{}", field_query.name);
+            let url = parse(&WorksQuery::default().field_query(field_query));
+            let words = title.split_whitespace().collect::<Vec<_>>().join(" ");
+            prop_assert_eq!(Some(words), param(&url, &key));
+        }
+
+        #[test]
+        fn filter_value_round_trip(doi in any::<String>(), title in any::<String>()) {
+            let url = parse(
+                &WorksQuery::default()
+                    .filter(WorksFilter::Doi(doi.clone()))
+                    .filter(WorksFilter::ContainerTitle(title.clone())),
+            );
+            prop_assert_eq!(
+                Some(format!("doi:{},container-title:{}", doi, title)),
+                param(&url, "filter")
+            );
+        }
+
+        #[test]
+        fn cursor_round_trip(token in any::<String>()) {
+            let query = WorksQuery::default().result_control(WorkResultControl::cursor(&token));
+            prop_assert_eq!(Some(token), param(&parse(&query), "cursor"));
+        }
+
+        #[test]
+        fn parsed_route_round_trip(
+            term in any::<String>(),
+            author in any::<String>(),
+            title in any::<String>(),
+            token in any::<String>(),
+        ) {
+            let route = WorksQuery::new(&term)
+                .field_query(FieldQuery::author(&author))
+                .filter(WorksFilter::ContainerTitle(title))
+                .next_cursor(&token)
+                .route()
+                .unwrap();
+            prop_assert_eq!(&route, &route.parse::<WorksQuery>().unwrap().route().unwrap());
+        }
+
+        #[test]
+        fn doi_round_trip(prefix in "10\\.[0-9]{4,9}", suffix in "[^/]*") {
+            // dot segments are resolved by every url parser
+            prop_assume!(suffix != "." && suffix != "..");
+            let doi = format!("{}/{}", prefix, suffix);
+            let url = parse(&Works::doi(&doi));
+            prop_assert_eq!(format!("works/{}", doi), path(&url));
+            prop_assert!(url.query().is_none() && url.fragment().is_none());
+
+            let url = parse(&WorksQuery::default().into_combined_query::<Funders>(&doi));
+            prop_assert_eq!(format!("funders/{}/works", doi), path(&url));
+        }
+    }
+}
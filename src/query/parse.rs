@@ -0,0 +1,270 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::query::facet::{Facet, FacetCount};
+use crate::query::works::{FieldQuery, WorkField};
+use crate::query::{Component, Filter, Order, ResultControl, Sort};
+use percent_encoding::percent_decode_str;
+use serde::de::{value, Deserialize, IntoDeserializer};
+use std::str::FromStr;
+use url::Url;
+
+/// the crossref api routes without a scheme and host are resolved against
+const API_BASE: &str = "https://api.crossref.org";
+
+/// an `InvalidRoute` error with the `msg`
+pub(crate) fn invalid<T: Into<String>>(msg: T) -> Error {
+    ErrorKind::InvalidRoute { msg: msg.into() }.into()
+}
+
+/// parses an absolute url or a route like `/works?query=..` of the crossref api
+pub(crate) fn parse_url(s: &str) -> Result<Url> {
+    let url = match Url::parse(s) {
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            Url::parse(API_BASE).and_then(|base| base.join(s))
+        }
+        url => url,
+    };
+    url.map_err(|err| invalid(format!("`{}` is not a valid url: {}", s, err)))
+}
+
+/// parses the url of a query of the `component`
+///
+/// The routes of the queries of components other than `/works` are only their query string,
+/// these are accepted as well.
+pub(crate) fn parse_query_url(s: &str, component: Component) -> Result<Url> {
+    if Url::parse(s).is_ok() || s.starts_with('/') {
+        parse_url(s)
+    } else {
+        parse_url(&format!("/{}?{}", component.as_str(), s))
+    }
+}
+
+/// decodes a percent-encoded part of the url, `+` is a space in the query string
+fn decode(s: &str, in_query: bool) -> Result<String> {
+    let plus_decoded;
+    let s = if in_query {
+        plus_decoded = s.replace('+', " ");
+        plus_decoded.as_str()
+    } else {
+        s
+    };
+    percent_decode_str(s)
+        .decode_utf8()
+        .map(|s| s.into_owned())
+        .map_err(|_| invalid(format!("`{}` is not valid utf-8", s)))
+}
+
+/// the decoded segments of the path of the `url`
+pub(crate) fn path_segments(url: &Url) -> Result<Vec<String>> {
+    let path = url.path().trim_matches('/');
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    path.split('/')
+        .map(|segment| decode(segment, false))
+        .collect()
+}
+
+/// fails if the `url` is not the route of the `component` itself
+pub(crate) fn expect_component(url: &Url, component: Component) -> Result<()> {
+    if path_segments(url)? == [component.as_str()] {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "expected a `/{}` route but got `{}`",
+            component.as_str(),
+            url.path()
+        )))
+    }
+}
+
+/// the decoded key and the still encoded value of all parameters in the query string of the `url`
+fn raw_params(url: &Url) -> Result<Vec<(String, &str)>> {
+    url.query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = match pair.find('=') {
+                Some(idx) => (&pair[..idx], &pair[idx + 1..]),
+                None => (pair, ""),
+            };
+            Ok((decode(key, true)?, value))
+        })
+        .collect()
+}
+
+/// fails if the query string of the `url` has parameters, identifier routes take none
+pub(crate) fn expect_no_params(url: &Url) -> Result<()> {
+    for (key, _) in raw_params(url)? {
+        // the polite pool parameter is set by the client
+        if key != "mailto" {
+            return Err(invalid(format!(
+                "unexpected parameter `{}` for `{}`",
+                key,
+                url.path()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// parses the unit variant of the enum `T` with the serde `name`
+pub(crate) fn from_name<'de, T: Deserialize<'de>>(kind: &str, name: &'de str) -> Result<T> {
+    T::deserialize(name.into_deserializer())
+        .map_err(|_: value::Error| invalid(format!("unknown {} `{}`", kind, name)))
+}
+
+/// parses the `value` of the parameter `key`
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("invalid value `{}` for `{}`", value, key)))
+}
+
+/// the value of the filter `name`, fails if it has none
+pub(crate) fn filter_value<'a>(name: &str, value: Option<&'a str>) -> Result<&'a str> {
+    value.ok_or_else(|| invalid(format!("filter `{}` expects a value", name)))
+}
+
+/// parses the value of the filter `name`
+pub(crate) fn parse_filter_value<T: FromStr>(name: &str, value: Option<&str>) -> Result<T> {
+    parse_value(name, filter_value(name, value)?)
+}
+
+/// fails if the value of the flag filter `name` is set to anything but `true`
+pub(crate) fn expect_true(name: &str, value: Option<&str>) -> Result<()> {
+    match value {
+        None | Some("true") => Ok(()),
+        Some(value) => Err(invalid(format!(
+            "only `true` is supported for filter `{}`, got `{}`",
+            name, value
+        ))),
+    }
+}
+
+/// the decoded key and value of a `key:value` fragment, like a filter or facet
+fn fragment(fragment: &str) -> Result<(String, Option<String>)> {
+    match fragment.find(':') {
+        Some(idx) => Ok((
+            decode(&fragment[..idx], true)?,
+            Some(decode(&fragment[idx + 1..], true)?),
+        )),
+        None => Ok((decode(fragment, true)?, None)),
+    }
+}
+
+/// All parameters of the query string of a route
+pub(crate) struct Params<F> {
+    pub queries: Vec<String>,
+    pub field_queries: Vec<FieldQuery>,
+    pub filter: Vec<F>,
+    pub facets: Vec<FacetCount>,
+    pub select: Vec<WorkField>,
+    pub sort: Option<Sort>,
+    pub order: Option<Order>,
+    pub rows: Option<usize>,
+    pub offset: Option<usize>,
+    pub sample: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+impl<F: Filter> Params<F> {
+    /// parses the query string of the `url`, fails on unknown parameters and filters
+    pub(crate) fn parse(url: &Url) -> Result<Self> {
+        let mut params = Params {
+            queries: Vec::new(),
+            field_queries: Vec::new(),
+            filter: Vec::new(),
+            facets: Vec::new(),
+            select: Vec::new(),
+            sort: None,
+            order: None,
+            rows: None,
+            offset: None,
+            sample: None,
+            cursor: None,
+        };
+        for (key, value) in raw_params(url)? {
+            // multi value parameters are split before decoding, since the values may contain `,` and `:`
+            let fragments = value.split(',').filter(|fragment| !fragment.is_empty());
+            match key.as_str() {
+                "query" => params.queries.push(decode(value, true)?),
+                "filter" => {
+                    for filter in fragments {
+                        let (name, value) = fragment(filter)?;
+                        params
+                            .filter
+                            .push(F::from_fragment(&name, value.as_deref())?);
+                    }
+                }
+                "facet" => {
+                    for facet in fragments {
+                        let (name, count) = fragment(facet)?;
+                        let count = match count.as_deref() {
+                            None | Some("*") => None,
+                            Some(count) => Some(parse_value(&name, count)?),
+                        };
+                        params.facets.push(FacetCount {
+                            facet: from_name::<Facet>("facet", &name)?,
+                            count,
+                        });
+                    }
+                }
+                "select" => {
+                    for field in fragments {
+                        params
+                            .select
+                            .push(from_name("field", &decode(field, true)?)?);
+                    }
+                }
+                "sort" => params.sort = Some(decode(value, true)?.parse().map_err(invalid)?),
+                "order" => params.order = Some(decode(value, true)?.parse().map_err(invalid)?),
+                "rows" => params.rows = Some(parse_value(&key, value)?),
+                "offset" => params.offset = Some(parse_value(&key, value)?),
+                "sample" => params.sample = Some(parse_value(&key, value)?),
+                "cursor" => params.cursor = Some(decode(value, true)?),
+                // the polite pool parameter is set by the client
+                "mailto" => {}
+                field if field.starts_with("query.") => params.field_queries.push(FieldQuery {
+                    name: field["query.".len()..].to_string(),
+                    value: decode(value, true)?,
+                }),
+                key => return Err(invalid(format!("unknown parameter `{}`", key))),
+            }
+        }
+        Ok(params)
+    }
+
+    /// fails if the route uses parameters only the `/works` route supports
+    pub(crate) fn expect_common(&self) -> Result<()> {
+        let unsupported = if !self.field_queries.is_empty() {
+            "field queries are"
+        } else if !self.select.is_empty() {
+            "`select` is"
+        } else if self.cursor.is_some() {
+            "cursors are"
+        } else {
+            return Ok(());
+        };
+        Err(invalid(format!(
+            "{} only supported on the `/works` route",
+            unsupported
+        )))
+    }
+
+    /// the `ResultControl` the rows, offset and sample parameters amount to
+    pub(crate) fn result_control(&self) -> Result<Option<ResultControl>> {
+        match (self.rows, self.offset, self.sample) {
+            (None, None, None) => Ok(None),
+            (Some(rows), None, None) => Ok(Some(ResultControl::Rows(rows))),
+            (None, Some(offset), None) => Ok(Some(ResultControl::Offset(offset))),
+            (Some(rows), Some(offset), None) => {
+                Ok(Some(ResultControl::RowsOffset { rows, offset }))
+            }
+            (None, None, Some(sample)) => Ok(Some(ResultControl::Sample(sample))),
+            _ => Err(invalid(
+                "`sample` can not be combined with `rows` or `offset`",
+            )),
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use crate::error::Result;
 use crate::query::works::{WorksCombiner, WorksFilter, WorksIdentQuery, WorksQuery};
-use crate::query::{Component, CrossrefQuery, CrossrefRoute, ResourceComponent};
+use crate::query::{encode_ident, Component, CrossrefQuery, CrossrefRoute, ResourceComponent};
+use serde::{Deserialize, Serialize};
 
 /// constructs the request payload for the `/prefixes` route
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Prefixes {
     /// target a specific member at `/prefixes/{id}`
     Identifier(String),
@@ -14,7 +16,11 @@ pub enum Prefixes {
 impl CrossrefRoute for Prefixes {
     fn route(&self) -> Result<String> {
         match self {
-            Prefixes::Identifier(s) => Ok(format!("{}/{}", Component::Prefixes.route()?, s)),
+            Prefixes::Identifier(s) => Ok(format!(
+                "{}/{}",
+                Component::Prefixes.route()?,
+                encode_ident(s)
+            )),
             Prefixes::Works(combined) => Self::combined_route(combined),
         }
     }
@@ -24,4 +30,11 @@ impl CrossrefQuery for Prefixes {
     fn resource_component(self) -> ResourceComponent {
         ResourceComponent::Prefixes(self)
     }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Prefixes::Works(combined) => combined.query.validate(),
+            _ => Ok(()),
+        }
+    }
 }
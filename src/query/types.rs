@@ -1,6 +1,6 @@
 use crate::error::{Error, ErrorKind, Result};
 use crate::query::works::{WorksCombiner, WorksFilter, WorksIdentQuery, WorksQuery};
-use crate::query::{Component, CrossrefQuery, CrossrefRoute, ResourceComponent};
+use crate::query::{encode_ident, Component, CrossrefQuery, CrossrefRoute, ResourceComponent};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -150,7 +150,8 @@ impl FromStr for Type {
 }
 
 /// constructs the request payload for the `/types` route
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Types {
     /// every available type
     All,
@@ -164,7 +165,9 @@ impl CrossrefRoute for Types {
     fn route(&self) -> Result<String> {
         match self {
             Types::All => Component::Types.route(),
-            Types::Identifier(s) => Ok(format!("{}/{}", Component::Types.route()?, s)),
+            Types::Identifier(s) => {
+                Ok(format!("{}/{}", Component::Types.route()?, encode_ident(s)))
+            }
             Types::Works(combined) => Self::combined_route(combined),
         }
     }
@@ -174,6 +177,13 @@ impl CrossrefQuery for Types {
     fn resource_component(self) -> ResourceComponent {
         ResourceComponent::Types(self)
     }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Types::Works(combined) => combined.query.validate(),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
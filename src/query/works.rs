@@ -1,4 +1,4 @@
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorKind, Result};
 use crate::query::facet::FacetCount;
 use crate::query::types::Type;
 use crate::query::*;
@@ -7,16 +7,28 @@ use serde::Serializer as SerdeSerializer;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::str::FromStr;
 #[cfg(feature = "cli")]
 use structopt::StructOpt;
+use url::Url;
 
 /// Filters allow you to narrow queries. All filter results are lists
-#[derive(Debug, Clone)]
+///
+/// A filter serializes with the name crossref uses for it in the query string. Filters without a value
+/// serialize as that name, like `"has-orcid"`, all others as a map, like `{"from-pub-date": "2019-01-01"}`
+/// or `{"type": "journal-article"}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum WorksFilter {
     /// metadata which includes one or more funder entry
     HasFunder,
     /// metadata which include the `id` in FundRef data
     Funder(String),
+    /// metadata which includes one or more funder entries with a funder DOI
+    HasFunderDoi,
+    /// metadata where the funder DOIs were asserted by the publisher or by crossref
+    FunderDoiAssertedBy(AssertedBy),
     /// funder records where location = `{country name}`.
     /// Only works on `/funders` route
     Location(String),
@@ -62,21 +74,47 @@ pub enum WorksFilter {
     FromAcceptedDate(NaiveDate),
     /// metadata where accepted date is before (inclusive)
     UntilAcceptedDate(NaiveDate),
+    /// metadata where issued date is since (inclusive), the earliest known publication date
+    FromIssuedDate(NaiveDate),
+    /// metadata where issued date is before (inclusive), the earliest known publication date
+    UntilIssuedDate(NaiveDate),
+    /// metadata where the start date of the event, like a conference, is since (inclusive)
+    FromEventStartDate(NaiveDate),
+    /// metadata where the start date of the event, like a conference, is before (inclusive)
+    UntilEventStartDate(NaiveDate),
+    /// metadata where the end date of the event, like a conference, is since (inclusive)
+    FromEventEndDate(NaiveDate),
+    /// metadata where the end date of the event, like a conference, is before (inclusive)
+    UntilEventEndDate(NaiveDate),
+    /// metadata where approved date, like of a dissertation, is since (inclusive)
+    FromApprovedDate(NaiveDate),
+    /// metadata where approved date, like of a dissertation, is before (inclusive)
+    UntilApprovedDate(NaiveDate),
+    /// metadata where the award date of a grant is since (inclusive)
+    FromAwardedDate(NaiveDate),
+    /// metadata where the award date of a grant is before (inclusive)
+    UntilAwardedDate(NaiveDate),
     /// metadata that includes any `<license_ref>` elements.
     HasLicense,
     /// metadata where `<license_ref> value equals the value
+    #[serde(rename = "license.url")]
     LicenseUrl(String),
     /// metadata where the `<license_ref>`'s applies_to attribute is
+    #[serde(rename = "license.version")]
     LicenseVersion(String),
     /// metadata where difference between publication date and the `<license_ref>`'s start_date attribute is <= value (in days)
+    #[serde(rename = "license.delay")]
     LicenseDelay(i32),
     /// metadata that includes any full text `<resource>` elements
     HasFullText,
     /// metadata where `<resource>` element's content_version attribute is the value
+    #[serde(rename = "full-text.version")]
     FullTextVersion(String),
     /// metadata where `<resource>` element's content_type attribute is value (e.g. `application/pdf)`
+    #[serde(rename = "full-text.type")]
     FullTextType(String),
     /// metadata where `<resource>` link has one of the following intended applications: `text-mining`, `similarity-checking` or `unspecified`
+    #[serde(rename = "full-text.application")]
     FullTextApplication(String),
     /// metadata for works that have a list of references
     HasReferences,
@@ -98,6 +136,7 @@ pub enum WorksFilter {
     Isbn(String),
     /// metadata records whose type = value.
     /// Type must be an ID value from the list of types returned by the `/types` resource
+    #[serde(with = "type_id")]
     Type(Type),
     /// metadata records whose article or serial are mentioned in the given value.
     /// Currently the only supported value is `doaj`
@@ -108,10 +147,14 @@ pub enum WorksFilter {
     Updates(String),
     /// metadata for records that represent editorial updates
     IsUpdate,
+    /// metadata for records that represent editorial updates of the type
+    UpdateType(UpdateType),
     /// metadata for records that include a link to an editorial update policy
     HasUpdatePolicy,
     /// metadata for records with a publication title exactly with an exact match
     ContainerTitle(String),
+    /// metadata for posted content with a group title with an exact match
+    GroupTitle(String),
     /// metadata for records with an exact matching category label.
     /// Category labels come from [this list](https://www.elsevier.com/solutions/scopus/content) published by Scopus
     CategoryName(String),
@@ -119,9 +162,11 @@ pub enum WorksFilter {
     TypeName(String),
     /// metadata for records with a matching award number.
     /// Optionally combine with `award.funder`
+    #[serde(rename = "award.number")]
     AwardNumber(String),
     /// metadata for records with an award with matching funder.
     /// Optionally combine with `award.number`
+    #[serde(rename = "award.funder")]
     AwardFunder(String),
     /// metadata for records with any assertions
     HasAssertion,
@@ -133,13 +178,21 @@ pub enum WorksFilter {
     HasAffiliation,
     /// metadata for records with the given alternative ID,
     /// which may be a publisher-specific ID, or any other identifier a publisher may have provided
-    AlternativeId,
+    AlternativeId(String),
     /// metadata for records with a given article number
-    ArticleNumber,
+    ArticleNumber(String),
     /// metadata for records which include an abstract
     HasAbstract,
+    /// metadata for records which include a description, like of a dataset
+    HasDescription,
     /// metadata for records which include a clinical trial number
     HasClinicalTrialNumber,
+    /// metadata for records with a given clinical trial number
+    ClinicalTrialNumber(String),
+    /// metadata for records with one or more affiliations or funders with a ROR ID
+    HasRorId,
+    /// metadata for records with an affiliation or funder with the ROR ID, like `https://ror.org/04wxnsj81`
+    RorId(String),
     /// metadata where the publisher records a particular domain name as the location Crossmark content will appear
     ContentDomain(String),
     /// metadata where the publisher records a domain name location for Crossmark content
@@ -150,19 +203,99 @@ pub enum WorksFilter {
     HasRelation,
     /// One of the relation types from the Crossref relations schema
     /// (e.g. `is-referenced-by`, `is-parent-of`, `is-preprint-of`)
-    RelationType,
+    #[serde(rename = "relation.type")]
+    RelationType(String),
     /// Relations where the object identifier matches the identifier provided
-    RelationObject,
+    #[serde(rename = "relation.object")]
+    RelationObject(String),
     /// One of the identifier types from the Crossref relations schema (e.g. `doi`, `issn`)
+    #[serde(rename = "relation.object-type")]
     RelationObjectType(String),
 }
 
+/// Who asserted the funder DOIs of a work, the value of the `funder-doi-asserted-by` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AssertedBy {
+    /// the funder DOIs were deposited by the publisher
+    Publisher,
+    /// the funder DOIs were matched by crossref
+    Crossref,
+}
+
+impl AssertedBy {
+    /// the value of the filter
+    pub fn as_str(&self) -> &str {
+        match self {
+            AssertedBy::Publisher => "publisher",
+            AssertedBy::Crossref => "crossref",
+        }
+    }
+}
+
+/// The types of editorial updates, the value of the `update-type` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum UpdateType {
+    Addendum,
+    Clarification,
+    Correction,
+    Corrigendum,
+    Erratum,
+    ExpressionOfConcern,
+    NewEdition,
+    NewVersion,
+    PartialRetraction,
+    Removal,
+    Retraction,
+    Withdrawal,
+}
+
+impl UpdateType {
+    /// the value of the filter
+    pub fn as_str(&self) -> &str {
+        match self {
+            UpdateType::Addendum => "addendum",
+            UpdateType::Clarification => "clarification",
+            UpdateType::Correction => "correction",
+            UpdateType::Corrigendum => "corrigendum",
+            UpdateType::Erratum => "erratum",
+            UpdateType::ExpressionOfConcern => "expression_of_concern",
+            UpdateType::NewEdition => "new_edition",
+            UpdateType::NewVersion => "new_version",
+            UpdateType::PartialRetraction => "partial_retraction",
+            UpdateType::Removal => "removal",
+            UpdateType::Retraction => "retraction",
+            UpdateType::Withdrawal => "withdrawal",
+        }
+    }
+}
+
+/// (de)serializes the `Type` of a filter as its id, like `journal-article`
+mod type_id {
+    use crate::query::types::Type;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ty: &Type, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(ty.id())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Type, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
 impl WorksFilter {
     /// the identifier for a the query key
     pub fn name(&self) -> &str {
         match self {
             WorksFilter::HasFunder => "has-funder",
             WorksFilter::Funder(_) => "funder",
+            WorksFilter::HasFunderDoi => "has-funder-doi",
+            WorksFilter::FunderDoiAssertedBy(_) => "funder-doi-asserted-by",
             WorksFilter::Location(_) => "location",
             WorksFilter::Prefix(_) => "prefix",
             WorksFilter::Member(_) => "member",
@@ -184,6 +317,16 @@ impl WorksFilter {
             WorksFilter::UntilPostedDate(_) => "until-posted-date",
             WorksFilter::FromAcceptedDate(_) => "from-accepted-date",
             WorksFilter::UntilAcceptedDate(_) => "until-accepted-date",
+            WorksFilter::FromIssuedDate(_) => "from-issued-date",
+            WorksFilter::UntilIssuedDate(_) => "until-issued-date",
+            WorksFilter::FromEventStartDate(_) => "from-event-start-date",
+            WorksFilter::UntilEventStartDate(_) => "until-event-start-date",
+            WorksFilter::FromEventEndDate(_) => "from-event-end-date",
+            WorksFilter::UntilEventEndDate(_) => "until-event-end-date",
+            WorksFilter::FromApprovedDate(_) => "from-approved-date",
+            WorksFilter::UntilApprovedDate(_) => "until-approved-date",
+            WorksFilter::FromAwardedDate(_) => "from-awarded-date",
+            WorksFilter::UntilAwardedDate(_) => "until-awarded-date",
             WorksFilter::HasLicense => "has-license",
             WorksFilter::LicenseUrl(_) => "license.url",
             WorksFilter::LicenseVersion(_) => "license.version",
@@ -206,8 +349,10 @@ impl WorksFilter {
             WorksFilter::Doi(_) => "doi",
             WorksFilter::Updates(_) => "updates",
             WorksFilter::IsUpdate => "is-update",
+            WorksFilter::UpdateType(_) => "update-type",
             WorksFilter::HasUpdatePolicy => "has-update-policy",
             WorksFilter::ContainerTitle(_) => "container-title",
+            WorksFilter::GroupTitle(_) => "group-title",
             WorksFilter::CategoryName(_) => "category-name",
             WorksFilter::TypeName(_) => "type-name",
             WorksFilter::AwardNumber(_) => "award.number",
@@ -216,27 +361,31 @@ impl WorksFilter {
             WorksFilter::AssertionGroup(_) => "assertion-group",
             WorksFilter::Assertion(_) => "assertion",
             WorksFilter::HasAffiliation => "has-affiliation",
-            WorksFilter::AlternativeId => "alternative-id",
-            WorksFilter::ArticleNumber => "article-number",
+            WorksFilter::AlternativeId(_) => "alternative-id",
+            WorksFilter::ArticleNumber(_) => "article-number",
             WorksFilter::HasAbstract => "has-abstract",
-            WorksFilter::HasClinicalTrialNumber => "has-clinical-trial-number	",
+            WorksFilter::HasDescription => "has-description",
+            WorksFilter::HasClinicalTrialNumber => "has-clinical-trial-number",
+            WorksFilter::ClinicalTrialNumber(_) => "clinical-trial-number",
+            WorksFilter::HasRorId => "has-ror-id",
+            WorksFilter::RorId(_) => "ror-id",
             WorksFilter::ContentDomain(_) => "content-domain",
             WorksFilter::HasContentDomain => "has-content-domain",
             WorksFilter::HasDomainRestriction => "has-domain-restriction",
             WorksFilter::HasRelation => "has-relation",
-            WorksFilter::RelationType => "relation.type",
-            WorksFilter::RelationObject => "relation.object",
+            WorksFilter::RelationType(_) => "relation.type",
+            WorksFilter::RelationObject(_) => "relation.object",
             WorksFilter::RelationObjectType(_) => "relation.object-type",
         }
     }
 }
 
 impl ParamFragment for WorksFilter {
-    fn key(&self) -> Cow<str> {
+    fn key(&self) -> Cow<'_, str> {
         Cow::Borrowed(self.name())
     }
 
-    fn value(&self) -> Option<Cow<str>> {
+    fn value(&self) -> Option<Cow<'_, str>> {
         match self {
             WorksFilter::Funder(s)
             | WorksFilter::Location(s)
@@ -262,7 +411,16 @@ impl ParamFragment for WorksFilter {
             | WorksFilter::AssertionGroup(s)
             | WorksFilter::Assertion(s)
             | WorksFilter::ContentDomain(s)
+            | WorksFilter::GroupTitle(s)
+            | WorksFilter::AlternativeId(s)
+            | WorksFilter::ArticleNumber(s)
+            | WorksFilter::ClinicalTrialNumber(s)
+            | WorksFilter::RorId(s)
+            | WorksFilter::RelationType(s)
+            | WorksFilter::RelationObject(s)
             | WorksFilter::RelationObjectType(s) => Some(Cow::Borrowed(s.as_str())),
+            WorksFilter::FunderDoiAssertedBy(by) => Some(Cow::Borrowed(by.as_str())),
+            WorksFilter::UpdateType(update) => Some(Cow::Borrowed(update.as_str())),
             WorksFilter::ReferenceVisibility(vis) => Some(Cow::Borrowed(vis.as_str())),
             WorksFilter::FromIndexDate(d)
             | WorksFilter::UntilIndexDate(d)
@@ -281,19 +439,136 @@ impl ParamFragment for WorksFilter {
             | WorksFilter::FromPostedDate(d)
             | WorksFilter::UntilPostedDate(d)
             | WorksFilter::FromAcceptedDate(d)
-            | WorksFilter::UntilAcceptedDate(d) => {
+            | WorksFilter::UntilAcceptedDate(d)
+            | WorksFilter::FromIssuedDate(d)
+            | WorksFilter::UntilIssuedDate(d)
+            | WorksFilter::FromEventStartDate(d)
+            | WorksFilter::UntilEventStartDate(d)
+            | WorksFilter::FromEventEndDate(d)
+            | WorksFilter::UntilEventEndDate(d)
+            | WorksFilter::FromApprovedDate(d)
+            | WorksFilter::UntilApprovedDate(d)
+            | WorksFilter::FromAwardedDate(d)
+            | WorksFilter::UntilAwardedDate(d) => {
                 Some(Cow::Owned(d.format("%Y-%m-%d").to_string()))
             }
             WorksFilter::Type(t) => Some(Cow::Borrowed(t.id())),
+            WorksFilter::LicenseDelay(days) => Some(Cow::Owned(days.to_string())),
             _ => Some(Cow::Borrowed("true")),
         }
     }
 }
 
-impl Filter for WorksFilter {}
+impl Filter for WorksFilter {
+    fn from_fragment(key: &str, value: Option<&str>) -> Result<Self> {
+        let flag = |filter| parse::expect_true(key, value).map(|_| filter);
+        let string = || parse::filter_value(key, value).map(str::to_string);
+        let date = || parse::parse_filter_value(key, value);
+        match key {
+            "has-funder" => flag(WorksFilter::HasFunder),
+            "funder" => string().map(WorksFilter::Funder),
+            "has-funder-doi" => flag(WorksFilter::HasFunderDoi),
+            "funder-doi-asserted-by" => parse::filter_value(key, value)
+                .and_then(|by| parse::from_name("asserter", by))
+                .map(WorksFilter::FunderDoiAssertedBy),
+            "location" => string().map(WorksFilter::Location),
+            "prefix" => string().map(WorksFilter::Prefix),
+            "member" => string().map(WorksFilter::Member),
+            "from-index-date" => date().map(WorksFilter::FromIndexDate),
+            "until-index-date" => date().map(WorksFilter::UntilIndexDate),
+            "from-deposit-date" => date().map(WorksFilter::FromDepositDate),
+            "until-deposit-date" => date().map(WorksFilter::UntilDepositDate),
+            "from-update-date" => date().map(WorksFilter::FromUpdateDate),
+            "until-update-date" => date().map(WorksFilter::UntilUpdateDate),
+            "from-created-date" => date().map(WorksFilter::FromCreatedDate),
+            "until-created-date" => date().map(WorksFilter::UntilCreatedDate),
+            "from-pub-date" => date().map(WorksFilter::FromPubDate),
+            "until-pub-date" => date().map(WorksFilter::UntilPubDate),
+            "from-online-pub-date" => date().map(WorksFilter::FromOnlinePubDate),
+            "until-online-pub-date" => date().map(WorksFilter::UntilOnlinePubDate),
+            "from-print-pub-date" => date().map(WorksFilter::FromPrintPubDate),
+            "until-print-pub-date" => date().map(WorksFilter::UntilPrintPubDate),
+            "from-posted-date" => date().map(WorksFilter::FromPostedDate),
+            "until-posted-date" => date().map(WorksFilter::UntilPostedDate),
+            "from-accepted-date" => date().map(WorksFilter::FromAcceptedDate),
+            "until-accepted-date" => date().map(WorksFilter::UntilAcceptedDate),
+            "from-issued-date" => date().map(WorksFilter::FromIssuedDate),
+            "until-issued-date" => date().map(WorksFilter::UntilIssuedDate),
+            "from-event-start-date" => date().map(WorksFilter::FromEventStartDate),
+            "until-event-start-date" => date().map(WorksFilter::UntilEventStartDate),
+            "from-event-end-date" => date().map(WorksFilter::FromEventEndDate),
+            "until-event-end-date" => date().map(WorksFilter::UntilEventEndDate),
+            "from-approved-date" => date().map(WorksFilter::FromApprovedDate),
+            "until-approved-date" => date().map(WorksFilter::UntilApprovedDate),
+            "from-awarded-date" => date().map(WorksFilter::FromAwardedDate),
+            "until-awarded-date" => date().map(WorksFilter::UntilAwardedDate),
+            "has-license" => flag(WorksFilter::HasLicense),
+            "license.url" => string().map(WorksFilter::LicenseUrl),
+            "license.version" => string().map(WorksFilter::LicenseVersion),
+            "license.delay" => parse::parse_filter_value(key, value).map(WorksFilter::LicenseDelay),
+            "has-full-text" => flag(WorksFilter::HasFullText),
+            "full-text.version" => string().map(WorksFilter::FullTextVersion),
+            "full-text.type" => string().map(WorksFilter::FullTextType),
+            "full-text.application" => string().map(WorksFilter::FullTextApplication),
+            "has-references" => flag(WorksFilter::HasReferences),
+            "reference-visibility" => parse::filter_value(key, value)
+                .and_then(|vis| parse::from_name("visibility", vis))
+                .map(WorksFilter::ReferenceVisibility),
+            "has-archive" => flag(WorksFilter::HasArchive),
+            "archive" => string().map(WorksFilter::Archive),
+            "has-orcid" => flag(WorksFilter::HasOrcid),
+            "has-authenticated-orcid" => flag(WorksFilter::HasAuthenticatedOrcid),
+            "orcid" => string().map(WorksFilter::Orcid),
+            "issn" => string().map(WorksFilter::Issn),
+            "isbn" => string().map(WorksFilter::Isbn),
+            "type" => parse::filter_value(key, value)?
+                .parse()
+                .map(WorksFilter::Type),
+            "directory" => string().map(WorksFilter::Directory),
+            "doi" => string().map(WorksFilter::Doi),
+            "updates" => string().map(WorksFilter::Updates),
+            "is-update" => flag(WorksFilter::IsUpdate),
+            "update-type" => parse::filter_value(key, value)
+                .and_then(|update| parse::from_name("update type", update))
+                .map(WorksFilter::UpdateType),
+            "has-update-policy" => flag(WorksFilter::HasUpdatePolicy),
+            "container-title" => string().map(WorksFilter::ContainerTitle),
+            "group-title" => string().map(WorksFilter::GroupTitle),
+            "category-name" => string().map(WorksFilter::CategoryName),
+            "type-name" => string().map(WorksFilter::TypeName),
+            "award.number" => string().map(WorksFilter::AwardNumber),
+            "award.funder" => string().map(WorksFilter::AwardFunder),
+            "has-assertion" => flag(WorksFilter::HasAssertion),
+            "assertion-group" => string().map(WorksFilter::AssertionGroup),
+            "assertion" => string().map(WorksFilter::Assertion),
+            "has-affiliation" => flag(WorksFilter::HasAffiliation),
+            "alternative-id" => string().map(WorksFilter::AlternativeId),
+            "article-number" => string().map(WorksFilter::ArticleNumber),
+            "has-abstract" => flag(WorksFilter::HasAbstract),
+            "has-description" => flag(WorksFilter::HasDescription),
+            "has-clinical-trial-number" => flag(WorksFilter::HasClinicalTrialNumber),
+            "clinical-trial-number" => string().map(WorksFilter::ClinicalTrialNumber),
+            "has-ror-id" => flag(WorksFilter::HasRorId),
+            "ror-id" => string().map(WorksFilter::RorId),
+            "content-domain" => string().map(WorksFilter::ContentDomain),
+            "has-content-domain" => flag(WorksFilter::HasContentDomain),
+            "has-domain-restriction" => flag(WorksFilter::HasDomainRestriction),
+            "has-relation" => flag(WorksFilter::HasRelation),
+            "relation.type" => string().map(WorksFilter::RelationType),
+            "relation.object" => string().map(WorksFilter::RelationObject),
+            "relation.object-type" => string().map(WorksFilter::RelationObjectType),
+            name => Err(ErrorKind::UnknownFilter {
+                name: name.to_string(),
+            }
+            .into()),
+        }
+    }
+}
 
 /// Field queries are available on the `/works` route and allow for queries that match only particular fields of metadata.
-#[derive(Debug, Clone)]
+///
+/// Serializes like `{"name": "author", "value": "richard feynman"}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "cli", derive(StructOpt))]
 pub struct FieldQuery {
     /// match any only particular fields of metadata.
@@ -371,16 +646,20 @@ impl FieldQuery {
 }
 
 impl CrossrefQueryParam for FieldQuery {
-    fn param_key(&self) -> Cow<str> {
-        Cow::Borrowed(&self.name)
+    fn param_key(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("query.{}", self.name))
     }
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         Some(Cow::Owned(format_query(&self.value)))
     }
 }
 
 /// limits from where and how many `Work` items should be returned
-#[derive(Debug, Clone)]
+///
+/// Serializes like `{"standard": {"rows": 20}}` or `{"cursor": {"token": "...", "rows": 20}}`,
+/// a cursor without a `token` starts deep paging.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum WorkResultControl {
     /// use the standard ResultControl available for all components
     Standard(ResultControl),
@@ -418,23 +697,25 @@ impl Default for WorkResultControl {
 }
 
 impl CrossrefQueryParam for WorkResultControl {
-    fn param_key(&self) -> Cow<str> {
+    fn param_key(&self) -> Cow<'_, str> {
         match self {
             WorkResultControl::Standard(s) => s.param_key(),
-            WorkResultControl::Cursor { token, .. } => Cow::Owned(format!(
-                "cursor={}",
-                token.as_ref().map(String::as_str).unwrap_or("*")
-            )),
+            WorkResultControl::Cursor { .. } => Cow::Borrowed("cursor"),
         }
     }
 
-    fn param_value(&self) -> Option<Cow<str>> {
+    fn param_value(&self) -> Option<Cow<'_, str>> {
         match self {
             WorkResultControl::Standard(s) => s.param_value(),
-            WorkResultControl::Cursor { rows, .. } => match rows {
-                Some(r) => Some(Cow::Owned(format!("rows={}", r))),
-                _ => None,
-            },
+            // the rows are a separate parameter in the query string
+            WorkResultControl::Cursor { token, rows } => {
+                let token = token.as_ref().map(encode_value);
+                let token = token.as_deref().unwrap_or("*");
+                Some(Cow::Owned(match rows {
+                    Some(rows) => format!("{}&rows={}", token, rows),
+                    None => token.to_string(),
+                }))
+            }
         }
     }
 }
@@ -458,7 +739,8 @@ impl CrossrefQueryParam for WorkResultControl {
 ///
 /// let works = Works::agency_for_doi("10.1037/0003-066X.59.1.29");
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Works {
     /// target a Work by a specific id
     Identifier(String),
@@ -482,8 +764,14 @@ impl Works {
 impl CrossrefRoute for Works {
     fn route(&self) -> Result<String> {
         match self {
-            Works::Identifier(s) => Ok(format!("{}/{}", Component::Works.route()?, s)),
-            Works::Agency(s) => Ok(format!("{}/{}/agency", Component::Works.route()?, s)),
+            Works::Identifier(s) => {
+                Ok(format!("{}/{}", Component::Works.route()?, encode_ident(s)))
+            }
+            Works::Agency(s) => Ok(format!(
+                "{}/{}/agency",
+                Component::Works.route()?,
+                encode_ident(s)
+            )),
             Works::Query(query) => query.route(),
         }
     }
@@ -493,10 +781,21 @@ impl CrossrefQuery for Works {
     fn resource_component(self) -> ResourceComponent {
         ResourceComponent::Works(self)
     }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Works::Query(query) => query.validate(),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Wraps queries that target `WorkList`, either directly or combined
-#[derive(Debug, Clone)]
+///
+/// Serializes like `{"works": {...}}` or
+/// `{"combined": {"primary-component": "members", "ident": {"id": "98", "query": {...}}}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 #[allow(missing_docs)]
 pub enum WorkListQuery {
     /// Target `Works` directly
@@ -524,11 +823,16 @@ impl WorkListQuery {
             WorkListQuery::Combined { ident, .. } => &mut ident.query,
         }
     }
+
+    /// checks the underlying `WorksQuery` with [`WorksQuery::validate`]
+    pub fn validate(&self) -> Result<()> {
+        self.query().validate()
+    }
 }
 
-impl Into<WorkListQuery> for WorksQuery {
-    fn into(self) -> WorkListQuery {
-        WorkListQuery::Works(self)
+impl From<WorksQuery> for WorkListQuery {
+    fn from(query: WorksQuery) -> Self {
+        WorkListQuery::Works(query)
     }
 }
 
@@ -548,7 +852,7 @@ impl CrossrefRoute for WorkListQuery {
             } => Ok(format!(
                 "{}/{}{}",
                 primary_component.route()?,
-                ident.id,
+                encode_ident(&ident.id),
                 ident.query.route()?
             )),
         }
@@ -572,6 +876,10 @@ impl CrossrefQuery for WorkListQuery {
             },
         }
     }
+
+    fn validate(&self) -> Result<()> {
+        WorkListQuery::validate(self)
+    }
 }
 
 /// Target `Works` as secondary resource component
@@ -593,7 +901,7 @@ impl CrossrefQuery for WorkListQuery {
 ///
 /// ```
 /// helper struct to capture an id for a `Component` other than `/works` and an additional query for the `/works` route
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorksIdentQuery {
     /// the id of an component item
     pub id: String,
@@ -624,7 +932,7 @@ pub trait WorksCombiner {
         Ok(format!(
             "{}/{}{}",
             Self::primary_component().route()?,
-            ident.id,
+            encode_ident(&ident.id),
             ident.query.route()?
         ))
     }
@@ -714,7 +1022,7 @@ impl WorksQuery {
     /// ```
     /// add a bunch of free form query terms
     pub fn field_queries(mut self, queries: Vec<FieldQuery>) -> Self {
-        self.field_queries.extend(queries.into_iter());
+        self.field_queries.extend(queries);
         self
     }
 
@@ -742,6 +1050,26 @@ impl WorksQuery {
         self
     }
 
+    /// Return only the `fields` of each work
+    ///
+    /// The items of the response lack all other fields, use [`crate::Crossref::partial_works`] to request them.
+    ///
+    /// # Example
+    ///
+    /// ```edition2018
+    /// use crossref::{WorkField, WorksQuery};
+    ///
+    /// let query = WorksQuery::new("ontologies").select(vec![WorkField::Doi, WorkField::Title]);
+    /// ```
+    pub fn select<I: IntoIterator<Item = WorkField>>(mut self, fields: I) -> Self {
+        for field in fields {
+            if !self.select.contains(&field) {
+                self.select.push(field);
+            }
+        }
+        self
+    }
+
     /// set the cursor for result control deep paging
     pub fn next_cursor(mut self, cursor: &str) -> Self {
         let rows = match self.result_control {
@@ -766,6 +1094,40 @@ impl WorksQuery {
         self
     }
 
+    /// checks the query against the rules of the `/works` route, before any request is made
+    ///
+    /// # Example
+    ///
+    /// ```edition2018
+    /// use crossref::{ErrorKind, Violation, WorkResultControl, WorksQuery};
+    ///
+    /// let query = WorksQuery::random(10).result_control(WorkResultControl::new_cursor());
+    /// match query.validate().unwrap_err().kind() {
+    ///     ErrorKind::InvalidQuery { violation } => assert_eq!(&Violation::SampleWithCursor, violation),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with `InvalidQuery` if the rows, offset or sample exceed the limits of crossref,
+    /// or if a sample is combined with a cursor
+    pub fn validate(&self) -> Result<()> {
+        if let Some(sample) = self.sample {
+            validate_sample(sample)?;
+        }
+        match &self.result_control {
+            Some(WorkResultControl::Standard(rc)) => rc.validate(Component::Works),
+            Some(WorkResultControl::Cursor { .. }) if self.sample.is_some() => {
+                Err(Violation::SampleWithCursor.into())
+            }
+            Some(WorkResultControl::Cursor {
+                rows: Some(rows), ..
+            }) => validate_rows(*rows),
+            _ => Ok(()),
+        }
+    }
+
     /// Wrap the query in a combined query.
     ///
     /// # Example
@@ -802,6 +1164,189 @@ impl WorksQuery {
     }
 }
 
+/// The fields of a `Work` that can be selected with [`WorksQuery::select`]
+///
+/// Each field is named like the field of `Work` it selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WorkField {
+    /// Name of work's publisher
+    Publisher,
+    /// Work titles, including translated titles
+    Title,
+    /// Work titles in the work's original publication language
+    OriginalTitle,
+    /// Short work titles
+    ShortTitle,
+    /// Abstract as a JSON string or a JATS XML snippet encoded into a JSON string
+    Abstract,
+    /// Count of outbound references deposited with Crossref
+    ReferenceCount,
+    /// Count of outbound references deposited with Crossref, the older name of `reference-count`
+    ReferencesCount,
+    /// Count of inbound references deposited with Crossref
+    IsReferencedByCount,
+    /// DOI prefix identifier
+    Prefix,
+    /// DOI of the work
+    #[serde(rename = "DOI")]
+    Doi,
+    /// URL form of the work's DOI
+    #[serde(rename = "URL")]
+    Url,
+    /// Member identifier
+    Member,
+    /// Type id of the work
+    Type,
+    /// the day this work entry was created
+    Created,
+    /// Date on which the work metadata was most recently updated
+    Deposited,
+    /// the relevance score of the work
+    Score,
+    /// Date on which the work metadata was most recently indexed
+    Indexed,
+    /// Earliest of `published-print` and `published-online`
+    Issued,
+    /// Date on which posted content was made available online
+    Posted,
+    /// Date on which a work was accepted
+    Accepted,
+    /// Work subtitles, including original language and translated
+    Subtitle,
+    /// Full titles of the containing work
+    ContainerTitle,
+    /// Abbreviated titles of the containing work
+    ShortContainerTitle,
+    /// Group title for posted content
+    GroupTitle,
+    /// Issue number of an article's journal
+    Issue,
+    /// Volume number of an article's journal
+    Volume,
+    /// Pages numbers of an article within its journal
+    Page,
+    /// the number of the corresponding article
+    ArticleNumber,
+    /// Date on which the work was published in print
+    PublishedPrint,
+    /// Date on which the work was published online
+    PublishedOnline,
+    /// Subject category names
+    Subject,
+    /// ISSNs of the work
+    #[serde(rename = "ISSN")]
+    Issn,
+    /// List of ISSNs with ISSN type information
+    IssnType,
+    /// ISBNs of the work
+    #[serde(rename = "ISBN")]
+    Isbn,
+    /// how the work is licensed
+    License,
+    /// the funders of the work
+    Funder,
+    /// the assertions of the work
+    Assertion,
+    /// the authors of the work
+    Author,
+    /// the editors of the work
+    Editor,
+    /// the chairs of the work
+    Chair,
+    /// the translators of the work
+    Translator,
+    /// the works this work updates
+    UpdateTo,
+    /// Link to an update policy covering Crossmark updates for this work
+    UpdatePolicy,
+    /// URLs to full-text locations
+    Link,
+    /// the clinical trial numbers of the work
+    ClinicalTrialNumber,
+    /// Other identifiers for the work provided by the depositing member
+    AlternativeId,
+    /// List of references made by the work
+    Reference,
+    /// Information on domains that support Crossmark for this work
+    ContentDomain,
+    /// Relations to other works
+    Relation,
+}
+
+impl WorkField {
+    /// the name crossref uses for the field
+    pub fn as_str(&self) -> &str {
+        match self {
+            WorkField::Publisher => "publisher",
+            WorkField::Title => "title",
+            WorkField::OriginalTitle => "original-title",
+            WorkField::ShortTitle => "short-title",
+            WorkField::Abstract => "abstract",
+            WorkField::ReferenceCount => "reference-count",
+            WorkField::ReferencesCount => "references-count",
+            WorkField::IsReferencedByCount => "is-referenced-by-count",
+            WorkField::Prefix => "prefix",
+            WorkField::Doi => "DOI",
+            WorkField::Url => "URL",
+            WorkField::Member => "member",
+            WorkField::Type => "type",
+            WorkField::Created => "created",
+            WorkField::Deposited => "deposited",
+            WorkField::Score => "score",
+            WorkField::Indexed => "indexed",
+            WorkField::Issued => "issued",
+            WorkField::Posted => "posted",
+            WorkField::Accepted => "accepted",
+            WorkField::Subtitle => "subtitle",
+            WorkField::ContainerTitle => "container-title",
+            WorkField::ShortContainerTitle => "short-container-title",
+            WorkField::GroupTitle => "group-title",
+            WorkField::Issue => "issue",
+            WorkField::Volume => "volume",
+            WorkField::Page => "page",
+            WorkField::ArticleNumber => "article-number",
+            WorkField::PublishedPrint => "published-print",
+            WorkField::PublishedOnline => "published-online",
+            WorkField::Subject => "subject",
+            WorkField::Issn => "ISSN",
+            WorkField::IssnType => "issn-type",
+            WorkField::Isbn => "ISBN",
+            WorkField::License => "license",
+            WorkField::Funder => "funder",
+            WorkField::Assertion => "assertion",
+            WorkField::Author => "author",
+            WorkField::Editor => "editor",
+            WorkField::Chair => "chair",
+            WorkField::Translator => "translator",
+            WorkField::UpdateTo => "update-to",
+            WorkField::UpdatePolicy => "update-policy",
+            WorkField::Link => "link",
+            WorkField::ClinicalTrialNumber => "clinical-trial-number",
+            WorkField::AlternativeId => "alternative-id",
+            WorkField::Reference => "reference",
+            WorkField::ContentDomain => "content-domain",
+            WorkField::Relation => "relation",
+        }
+    }
+}
+
+impl CrossrefQueryParam for Vec<WorkField> {
+    fn param_key(&self) -> Cow<'_, str> {
+        Cow::Borrowed("select")
+    }
+
+    /// the fields are concat with `,`
+    fn param_value(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(
+            self.iter()
+                .map(WorkField::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+        ))
+    }
+}
+
 /// Used to construct a query that targets crossref `Works` elements
 ///
 /// # Example
@@ -813,8 +1358,36 @@ impl WorksQuery {
 /// let query = WorksQuery::new("machine learning").order(Order::Desc);
 /// ```
 ///
+/// # Serialization
+///
+/// A query serializes to a map of its fields in kebab-case, each of them is optional.
+/// Filters, facets, sort and order use the names of the query string, so a query can be kept in a config file:
+///
+/// ```edition2018
+/// use crossref::{CrossrefRoute, WorksQuery};
+///
+/// let query: WorksQuery = toml::from_str(
+///     r#"
+/// free-form-queries = ["machine learning"]
+/// field-queries = [{ name = "author", value = "smith" }]
+/// filter = ["has-orcid", { from-pub-date = "2019-01-01" }, { type = "journal-article" }]
+/// facets = [{ facet = "type-name", count = 10 }]
+/// select = ["DOI", "title"]
+/// sort = "published"
+/// order = "desc"
+/// result-control = { cursor = { rows = 100 } }
+/// "#,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     "/works?query=machine+learning&query.author=smith&filter=has-orcid:true,from-pub-date:2019-01-01,type:journal-article&facet=type-name:10&select=DOI,title&sort=published&order=desc&cursor=*&rows=100",
+///     query.route().unwrap()
+/// );
+/// ```
+///
 /// Each query parameter is ANDed
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
 pub struct WorksQuery {
     /// search by non specific query
     pub free_form_queries: Vec<String>,
@@ -833,6 +1406,8 @@ pub struct WorksQuery {
     /// request random dois
     /// if set all other parameters are ignored
     pub sample: Option<usize>,
+    /// return only these fields of each work
+    pub select: Vec<WorkField>,
 }
 
 impl CrossrefRoute for WorksQuery {
@@ -840,7 +1415,7 @@ impl CrossrefRoute for WorksQuery {
         let mut params = Vec::new();
 
         if let Some(sample) = self.sample {
-            return Ok(format!("sample={}", sample));
+            return Ok(format!("{}?sample={}", Component::Works.route()?, sample));
         }
 
         if !self.free_form_queries.is_empty() {
@@ -858,6 +1433,9 @@ impl CrossrefRoute for WorksQuery {
         if !self.facets.is_empty() {
             params.push(self.facets.param());
         }
+        if !self.select.is_empty() {
+            params.push(self.select.param());
+        }
         if let Some(sort) = &self.sort {
             params.push(sort.param());
         }
@@ -876,6 +1454,88 @@ impl CrossrefRoute for WorksQuery {
     }
 }
 
+impl WorksQuery {
+    /// the query of the parameters of the `url`, regardless of its path
+    fn from_params(url: &Url) -> Result<Self> {
+        let mut params = parse::Params::<WorksFilter>::parse(url)?;
+        // a sample is not a result control of a `WorksQuery`
+        let sample = params.sample.take();
+        let result_control = match params.cursor.take() {
+            Some(_) if params.offset.is_some() => {
+                return Err(parse::invalid("`cursor` can not be combined with `offset`"))
+            }
+            Some(token) => Some(WorkResultControl::Cursor {
+                token: if token == "*" { None } else { Some(token) },
+                rows: params.rows,
+            }),
+            None => params.result_control()?.map(WorkResultControl::Standard),
+        };
+        Ok(WorksQuery {
+            free_form_queries: params.queries,
+            field_queries: params.field_queries,
+            filter: params.filter,
+            sort: params.sort,
+            order: params.order,
+            facets: params.facets,
+            result_control,
+            sample,
+            select: params.select,
+        })
+    }
+}
+
+/// Parses a `/works` url or route
+///
+/// # Example
+///
+/// ```edition2018
+/// use crossref::{CrossrefRoute, WorksQuery};
+///
+/// let query: WorksQuery = "https://api.crossref.org/works?query.author=smith&filter=has-orcid:true&rows=20"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(
+///     "/works?query.author=smith&filter=has-orcid:true&rows=20",
+///     query.route().unwrap()
+/// );
+/// ```
+impl FromStr for WorksQuery {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(parse::parse_url(s)?)
+    }
+}
+
+impl TryFrom<Url> for WorksQuery {
+    type Error = Error;
+
+    fn try_from(url: Url) -> Result<Self> {
+        parse::expect_component(&url, Component::Works)?;
+        WorksQuery::from_params(&url)
+    }
+}
+
+impl WorkListQuery {
+    /// the query of a `/works` url, or of the `/works` route of another component like `/members/{id}/works`
+    pub(crate) fn from_url(url: &Url) -> Result<Self> {
+        let segments = parse::path_segments(url)?;
+        match segments.as_slice() {
+            [works] if works == "works" => WorksQuery::from_params(url).map(WorkListQuery::Works),
+            [component, id, works] if component != "works" && works == "works" => {
+                Ok(WorkListQuery::Combined {
+                    primary_component: parse::from_name("component", component)?,
+                    ident: WorksIdentQuery::new(id.as_str(), WorksQuery::from_params(url)?),
+                })
+            }
+            _ => Err(parse::invalid(format!(
+                "expected a route to works but got `{}`",
+                url.path()
+            ))),
+        }
+    }
+}
+
 impl CrossrefParams for WorksQuery {
     type Filter = WorksFilter;
 
@@ -913,4 +1573,351 @@ mod tests {
 
         assert_eq!("/works/10.1037/0003-066X.59.1.29", &works.route().unwrap())
     }
+
+    #[test]
+    fn parse_works_query() {
+        let route = "/works?query=renear+ontologies&query.author=carberry&filter=has-orcid:true,from-pub-date:2019-01-01,type:journal-article,container-title:Nature%2C+Physics&facet=orcid:100,type-name:*&select=DOI,title&sort=is-referenced-by-count&order=desc&cursor=*&rows=20";
+        let query: WorksQuery = route.parse().unwrap();
+        assert_eq!(route, query.route().unwrap());
+        assert_eq!(
+            vec!["renear ontologies".to_string()],
+            query.free_form_queries
+        );
+        assert_eq!("author", query.field_queries[0].name);
+        match &query.filter[3] {
+            WorksFilter::ContainerTitle(title) => assert_eq!("Nature, Physics", title),
+            other => panic!("unexpected filter {:?}", other),
+        }
+        match query.result_control {
+            Some(WorkResultControl::Cursor { token, rows }) => {
+                assert_eq!((None, Some(20)), (token, rows))
+            }
+            other => panic!("unexpected result control {:?}", other),
+        }
+
+        for route in &[
+            "/works?sample=10",
+            "/works?filter=until-index-date:2020-12-31&rows=5&offset=10",
+            "/works?cursor=AoJ%2Bpeb3%2FfUCPw%3D%3D",
+        ] {
+            assert_eq!(
+                *route,
+                route.parse::<WorksQuery>().unwrap().route().unwrap()
+            );
+        }
+
+        match "/works?filter=has-orcid:true,is-cited:true"
+            .parse::<WorksQuery>()
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::UnknownFilter { name } => assert_eq!("is-cited", name),
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!("/works?filter=from-pub-date:yesterday"
+            .parse::<WorksQuery>()
+            .is_err());
+        assert!("/works?filter=has-orcid:false"
+            .parse::<WorksQuery>()
+            .is_err());
+        assert!("/works?cursor=*&offset=10".parse::<WorksQuery>().is_err());
+        assert!("/works?rows=5&colour=blue".parse::<WorksQuery>().is_err());
+        assert!("/members/98/works".parse::<WorksQuery>().is_err());
+    }
+
+    #[test]
+    fn select_fields() {
+        let query = WorksQuery::new("ontologies").select(vec![
+            WorkField::Doi,
+            WorkField::IsReferencedByCount,
+            WorkField::ReferenceCount,
+            WorkField::Doi,
+        ]);
+        let route = "/works?query=ontologies&select=DOI,is-referenced-by-count,reference-count";
+        assert_eq!(route, &query.route().unwrap());
+        assert_eq!(query.select, route.parse::<WorksQuery>().unwrap().select);
+        assert_eq!("\"ISSN\"", serde_json::to_string(&WorkField::Issn).unwrap());
+    }
+
+    #[test]
+    fn filter_fragments() {
+        let date = NaiveDate::from_ymd_opt(2019, 3, 1).unwrap();
+        let s = |s: &str| s.to_string();
+        let filters = vec![
+            (WorksFilter::HasFunder, "has-funder:true"),
+            (
+                WorksFilter::Funder(s("10.13039/100000001")),
+                "funder:10.13039%2F100000001",
+            ),
+            (WorksFilter::HasFunderDoi, "has-funder-doi:true"),
+            (
+                WorksFilter::FunderDoiAssertedBy(AssertedBy::Publisher),
+                "funder-doi-asserted-by:publisher",
+            ),
+            (
+                WorksFilter::FunderDoiAssertedBy(AssertedBy::Crossref),
+                "funder-doi-asserted-by:crossref",
+            ),
+            (WorksFilter::Location(s("Germany")), "location:Germany"),
+            (WorksFilter::Prefix(s("10.1037")), "prefix:10.1037"),
+            (WorksFilter::Member(s("98")), "member:98"),
+            (
+                WorksFilter::FromIndexDate(date),
+                "from-index-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilIndexDate(date),
+                "until-index-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromDepositDate(date),
+                "from-deposit-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilDepositDate(date),
+                "until-deposit-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromUpdateDate(date),
+                "from-update-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilUpdateDate(date),
+                "until-update-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromCreatedDate(date),
+                "from-created-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilCreatedDate(date),
+                "until-created-date:2019-03-01",
+            ),
+            (WorksFilter::FromPubDate(date), "from-pub-date:2019-03-01"),
+            (WorksFilter::UntilPubDate(date), "until-pub-date:2019-03-01"),
+            (
+                WorksFilter::FromOnlinePubDate(date),
+                "from-online-pub-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilOnlinePubDate(date),
+                "until-online-pub-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromPrintPubDate(date),
+                "from-print-pub-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilPrintPubDate(date),
+                "until-print-pub-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromPostedDate(date),
+                "from-posted-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilPostedDate(date),
+                "until-posted-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromAcceptedDate(date),
+                "from-accepted-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilAcceptedDate(date),
+                "until-accepted-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromIssuedDate(date),
+                "from-issued-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilIssuedDate(date),
+                "until-issued-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromEventStartDate(date),
+                "from-event-start-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilEventStartDate(date),
+                "until-event-start-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromEventEndDate(date),
+                "from-event-end-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilEventEndDate(date),
+                "until-event-end-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromApprovedDate(date),
+                "from-approved-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilApprovedDate(date),
+                "until-approved-date:2019-03-01",
+            ),
+            (
+                WorksFilter::FromAwardedDate(date),
+                "from-awarded-date:2019-03-01",
+            ),
+            (
+                WorksFilter::UntilAwardedDate(date),
+                "until-awarded-date:2019-03-01",
+            ),
+            (WorksFilter::HasLicense, "has-license:true"),
+            (
+                WorksFilter::LicenseUrl(s("http://creativecommons.org/licenses/by/4.0/")),
+                "license.url:http%3A%2F%2Fcreativecommons.org%2Flicenses%2Fby%2F4.0%2F",
+            ),
+            (WorksFilter::LicenseVersion(s("vor")), "license.version:vor"),
+            (WorksFilter::LicenseDelay(30), "license.delay:30"),
+            (WorksFilter::HasFullText, "has-full-text:true"),
+            (
+                WorksFilter::FullTextVersion(s("am")),
+                "full-text.version:am",
+            ),
+            (
+                WorksFilter::FullTextType(s("application/pdf")),
+                "full-text.type:application%2Fpdf",
+            ),
+            (
+                WorksFilter::FullTextApplication(s("text-mining")),
+                "full-text.application:text-mining",
+            ),
+            (WorksFilter::HasReferences, "has-references:true"),
+            (
+                WorksFilter::ReferenceVisibility(Visibility::Open),
+                "reference-visibility:open",
+            ),
+            (WorksFilter::HasArchive, "has-archive:true"),
+            (WorksFilter::Archive(s("CLOCKSS")), "archive:CLOCKSS"),
+            (WorksFilter::HasOrcid, "has-orcid:true"),
+            (
+                WorksFilter::HasAuthenticatedOrcid,
+                "has-authenticated-orcid:true",
+            ),
+            (
+                WorksFilter::Orcid(s("0000-0002-1825-0097")),
+                "orcid:0000-0002-1825-0097",
+            ),
+            (WorksFilter::Issn(s("2041-1723")), "issn:2041-1723"),
+            (WorksFilter::Isbn(s("9780387310732")), "isbn:9780387310732"),
+            (
+                WorksFilter::Type(Type::JournalArticle),
+                "type:journal-article",
+            ),
+            (WorksFilter::Directory(s("DOAJ")), "directory:DOAJ"),
+            (
+                WorksFilter::Doi(s("10.1037/0003-066X.59.1.29")),
+                "doi:10.1037%2F0003-066X.59.1.29",
+            ),
+            (
+                WorksFilter::Updates(s("10.5555/12345678")),
+                "updates:10.5555%2F12345678",
+            ),
+            (WorksFilter::IsUpdate, "is-update:true"),
+            (
+                WorksFilter::UpdateType(UpdateType::ExpressionOfConcern),
+                "update-type:expression_of_concern",
+            ),
+            (
+                WorksFilter::UpdateType(UpdateType::Retraction),
+                "update-type:retraction",
+            ),
+            (WorksFilter::HasUpdatePolicy, "has-update-policy:true"),
+            (
+                WorksFilter::ContainerTitle(s("Nature Physics")),
+                "container-title:Nature+Physics",
+            ),
+            (
+                WorksFilter::GroupTitle(s("Life Sciences")),
+                "group-title:Life+Sciences",
+            ),
+            (
+                WorksFilter::CategoryName(s("Physics")),
+                "category-name:Physics",
+            ),
+            (
+                WorksFilter::TypeName(s("Journal Article")),
+                "type-name:Journal+Article",
+            ),
+            (
+                WorksFilter::AwardNumber(s("CBET-0756451")),
+                "award.number:CBET-0756451",
+            ),
+            (
+                WorksFilter::AwardFunder(s("10.13039/100000001")),
+                "award.funder:10.13039%2F100000001",
+            ),
+            (WorksFilter::HasAssertion, "has-assertion:true"),
+            (
+                WorksFilter::AssertionGroup(s("peer_review")),
+                "assertion-group:peer_review",
+            ),
+            (WorksFilter::Assertion(s("received")), "assertion:received"),
+            (WorksFilter::HasAffiliation, "has-affiliation:true"),
+            (
+                WorksFilter::AlternativeId(s("S0001")),
+                "alternative-id:S0001",
+            ),
+            (
+                WorksFilter::ArticleNumber(s("e1001")),
+                "article-number:e1001",
+            ),
+            (WorksFilter::HasAbstract, "has-abstract:true"),
+            (WorksFilter::HasDescription, "has-description:true"),
+            (
+                WorksFilter::HasClinicalTrialNumber,
+                "has-clinical-trial-number:true",
+            ),
+            (
+                WorksFilter::ClinicalTrialNumber(s("NCT00000001")),
+                "clinical-trial-number:NCT00000001",
+            ),
+            (WorksFilter::HasRorId, "has-ror-id:true"),
+            (
+                WorksFilter::RorId(s("https://ror.org/04wxnsj81")),
+                "ror-id:https%3A%2F%2Fror.org%2F04wxnsj81",
+            ),
+            (
+                WorksFilter::ContentDomain(s("psychoceramics.org")),
+                "content-domain:psychoceramics.org",
+            ),
+            (WorksFilter::HasContentDomain, "has-content-domain:true"),
+            (
+                WorksFilter::HasDomainRestriction,
+                "has-domain-restriction:true",
+            ),
+            (WorksFilter::HasRelation, "has-relation:true"),
+            (
+                WorksFilter::RelationType(s("is-preprint-of")),
+                "relation.type:is-preprint-of",
+            ),
+            (
+                WorksFilter::RelationObject(s("10.5555/12345678")),
+                "relation.object:10.5555%2F12345678",
+            ),
+            (
+                WorksFilter::RelationObjectType(s("doi")),
+                "relation.object-type:doi",
+            ),
+        ];
+        for (filter, fragment) in &filters {
+            assert_eq!(*fragment, filter.fragment(), "{:?}", filter);
+            let query: WorksQuery = format!("/works?filter={}", fragment).parse().unwrap();
+            assert_eq!(1, query.filter.len());
+            assert_eq!(*fragment, query.filter[0].fragment());
+        }
+
+        assert!("/works?filter=update-type:typo"
+            .parse::<WorksQuery>()
+            .is_err());
+        assert!("/works?filter=funder-doi-asserted-by:anyone"
+            .parse::<WorksQuery>()
+            .is_err());
+        assert!("/works?filter=relation.type".parse::<WorksQuery>().is_err());
+    }
 }
@@ -0,0 +1,312 @@
+use reqwest::header::HeaderMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// the header crossref uses to advertise how many requests are allowed per interval
+const LIMIT_HEADER: &str = "x-rate-limit-limit";
+/// the header crossref uses to advertise the interval the limit applies to
+const INTERVAL_HEADER: &str = "x-rate-limit-interval";
+
+/// A number of requests that are allowed within an interval
+///
+/// # Example
+///
+/// Allow at most 10 requests per second, even if crossref advertises a higher limit
+///
+/// ```edition2018
+/// # #[cfg(feature = "blocking")] {
+/// use crossref::{Crossref, RateLimit};
+/// # fn run() -> Result<(), crossref::Error> {
+/// let client = Crossref::builder()
+///     .rate_limit(RateLimit::per_second(10))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// how many requests are allowed per `interval`
+    pub requests: u32,
+    /// the time frame `requests` applies to
+    pub interval: Duration,
+}
+
+impl RateLimit {
+    /// create a new limit of `requests` per `interval`
+    pub fn new(requests: u32, interval: Duration) -> Self {
+        RateLimit { requests, interval }
+    }
+
+    /// create a new limit of `requests` per second
+    pub fn per_second(requests: u32) -> Self {
+        RateLimit::new(requests, Duration::from_secs(1))
+    }
+
+    /// the allowed requests per second, a limit of zero requests is treated as one
+    fn rate(&self) -> f64 {
+        let secs = self.interval.as_secs_f64();
+        if secs > 0.0 {
+            f64::from(self.requests.max(1)) / secs
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// reads the limit crossref advertises in the `X-Rate-Limit-Limit` and `X-Rate-Limit-Interval` headers
+    ///
+    /// A limit of zero requests is ignored, it would stall the client forever.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let requests = headers
+            .get(LIMIT_HEADER)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+            .filter(|requests| *requests > 0)?;
+        let interval = parse_interval(headers.get(INTERVAL_HEADER)?.to_str().ok()?)?;
+        Some(RateLimit::new(requests, interval))
+    }
+}
+
+/// parses an interval like `1s`, `500ms`, `1m` or `1h`, a bare number is taken as seconds
+fn parse_interval(interval: &str) -> Option<Duration> {
+    let interval = interval.trim();
+    let split = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(interval.len());
+    let (value, unit) = interval.split_at(split);
+    let value: u64 = value.parse().ok()?;
+    match unit.trim() {
+        "ms" => Some(Duration::from_millis(value)),
+        "" | "s" => Some(Duration::from_secs(value)),
+        "m" => value.checked_mul(60).map(Duration::from_secs),
+        "h" => value.checked_mul(60 * 60).map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+/// Spaces requests so that neither the limit crossref advertises nor the configured cap is exceeded.
+///
+/// The limiter is a token bucket: every request takes a token and tokens are refilled at the allowed rate.
+/// Until crossref advertised a limit and without a configured cap, requests are not delayed.
+///
+/// Clones share their state, so all clones of a client draw from the same bucket.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    /// create a new limiter that allows at most `cap` requests, regardless of what crossref advertises
+    pub fn with_cap(cap: Option<RateLimit>) -> Self {
+        RateLimiter {
+            bucket: Arc::new(Mutex::new(Bucket {
+                cap,
+                ..Bucket::default()
+            })),
+        }
+    }
+
+    /// the limit that is currently applied, if any
+    pub fn limit(&self) -> Option<RateLimit> {
+        self.lock().limit()
+    }
+
+    /// waits until another request is allowed
+    pub(crate) async fn acquire(&self) {
+        loop {
+            let wait = self.lock().take(Instant::now());
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+
+    /// learns the limit crossref advertises in the headers of a response
+    pub(crate) fn update(&self, headers: &HeaderMap) {
+        if let Some(limit) = RateLimit::from_headers(headers) {
+            self.lock().advertise(limit, Instant::now());
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        // the bucket holds no invariants a panicking thread could break
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// the state of the token bucket
+#[derive(Debug, Default)]
+struct Bucket {
+    /// the limit advertised by crossref
+    advertised: Option<RateLimit>,
+    /// the configured upper bound
+    cap: Option<RateLimit>,
+    /// available requests
+    tokens: f64,
+    /// when the tokens were refilled the last time
+    refilled: Option<Instant>,
+}
+
+impl Bucket {
+    /// the stricter of the advertised limit and the cap
+    fn limit(&self) -> Option<RateLimit> {
+        match (self.advertised, self.cap) {
+            (Some(advertised), Some(cap)) => Some(if cap.rate() < advertised.rate() {
+                cap
+            } else {
+                advertised
+            }),
+            (advertised, cap) => advertised.or(cap),
+        }
+    }
+
+    /// adds the tokens that accumulated since the last refill
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let capacity = f64::from(limit.requests.max(1));
+        let tokens = match self.refilled {
+            Some(refilled) => {
+                self.tokens + now.saturating_duration_since(refilled).as_secs_f64() * limit.rate()
+            }
+            // the first request under a limit starts with a full bucket
+            None => capacity,
+        };
+        self.tokens = tokens.min(capacity);
+        self.refilled = Some(now);
+    }
+
+    /// takes a token for a request, if none is available returns how long to wait for one
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        let limit = self.limit()?;
+        self.refill(limit, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            // a wait too long for a `Duration` can only come from an enormous interval
+            Some(
+                Duration::try_from_secs_f64((1.0 - self.tokens) / limit.rate())
+                    .unwrap_or(limit.interval),
+            )
+        }
+    }
+
+    /// replaces the advertised limit
+    fn advertise(&mut self, limit: RateLimit, now: Instant) {
+        if self.advertised == Some(limit) {
+            return;
+        }
+        if let Some(current) = self.limit() {
+            self.refill(current, now);
+        }
+        self.advertised = Some(limit);
+        if let Some(limit) = self.limit() {
+            self.tokens = self.tokens.min(f64::from(limit.requests.max(1)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn parse_advertised_limit() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(None, RateLimit::from_headers(&headers));
+        headers.insert(LIMIT_HEADER, HeaderValue::from_static("50"));
+        headers.insert(INTERVAL_HEADER, HeaderValue::from_static("1s"));
+        assert_eq!(
+            Some(RateLimit::per_second(50)),
+            RateLimit::from_headers(&headers)
+        );
+        assert_eq!(Some(Duration::from_millis(500)), parse_interval("500ms"));
+        assert_eq!(Some(Duration::from_secs(120)), parse_interval("2m"));
+        assert_eq!(None, parse_interval("2 fortnights"));
+    }
+
+    #[test]
+    fn unlimited_until_advertised() {
+        let mut bucket = Bucket::default();
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(None, bucket.take(now));
+        }
+    }
+
+    #[test]
+    fn spaces_requests() {
+        let mut bucket = Bucket::default();
+        let now = Instant::now();
+        bucket.advertise(RateLimit::per_second(2), now);
+
+        assert_eq!(None, bucket.take(now));
+        assert_eq!(None, bucket.take(now));
+        assert_eq!(Some(Duration::from_millis(500)), bucket.take(now));
+        assert_eq!(None, bucket.take(now + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn cap_is_stricter_than_advertised() {
+        let mut bucket = Bucket {
+            cap: Some(RateLimit::per_second(1)),
+            ..Bucket::default()
+        };
+        let now = Instant::now();
+        bucket.advertise(RateLimit::per_second(50), now);
+        assert_eq!(Some(RateLimit::per_second(1)), bucket.limit());
+
+        assert_eq!(None, bucket.take(now));
+        assert_eq!(Some(Duration::from_secs(1)), bucket.take(now));
+    }
+
+    #[test]
+    fn zero_limit_does_not_panic() {
+        let mut headers = HeaderMap::new();
+        headers.insert(LIMIT_HEADER, HeaderValue::from_static("0"));
+        headers.insert(INTERVAL_HEADER, HeaderValue::from_static("1s"));
+        assert_eq!(None, RateLimit::from_headers(&headers));
+
+        let mut bucket = Bucket {
+            cap: Some(RateLimit::per_second(0)),
+            ..Bucket::default()
+        };
+        let now = Instant::now();
+        assert_eq!(None, bucket.take(now));
+        assert_eq!(Some(Duration::from_secs(1)), bucket.take(now));
+    }
+
+    #[test]
+    fn huge_interval_does_not_panic() {
+        assert_eq!(None, parse_interval("18446744073709551615m"));
+        assert_eq!(None, parse_interval("5124095576030432h"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(LIMIT_HEADER, HeaderValue::from_static("1"));
+        headers.insert(
+            INTERVAL_HEADER,
+            HeaderValue::from_static("18446744073709551615"),
+        );
+        let limit = RateLimit::from_headers(&headers).unwrap();
+        let mut bucket = Bucket::default();
+        let now = Instant::now();
+        bucket.advertise(limit, now);
+        assert_eq!(None, bucket.take(now));
+        assert_eq!(Some(limit.interval), bucket.take(now));
+    }
+
+    #[test]
+    fn clones_share_the_bucket() {
+        let limiter = RateLimiter::default();
+        let clone = limiter.clone();
+        let mut headers = HeaderMap::new();
+        headers.insert(LIMIT_HEADER, HeaderValue::from_static("10"));
+        headers.insert(INTERVAL_HEADER, HeaderValue::from_static("1s"));
+        limiter.update(&headers);
+        assert_eq!(Some(RateLimit::per_second(10)), clone.limit());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::query::facet::Facet;
+use crate::query::Type;
+use crate::response::{FacetItem, FacetMap};
+use std::cmp::Ordering;
+use std::fmt;
+use url::Url;
+
+/// A value of a facet, typed where the value crossref returns has a meaning beyond its text
+///
+/// Values of the typed facets that crossref returns in an unexpected form are kept as `Text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FacetValue {
+    /// a year of the `published` facet
+    Year(i32),
+    /// a type of the `type-name` facet
+    Type(Type),
+    /// a url of the `license` facet
+    Url(Url),
+    /// the value of any other facet
+    Text(String),
+}
+
+impl FacetValue {
+    /// parses the `value` crossref returned for the `facet`
+    pub fn parse(facet: Facet, value: &str) -> Self {
+        let typed = match facet {
+            Facet::Published => value.parse().ok().map(FacetValue::Year),
+            // crossref returns the labels of the types, like `Journal Article`
+            Facet::TypeName => value
+                .to_lowercase()
+                .replace(' ', "-")
+                .parse()
+                .ok()
+                .map(FacetValue::Type),
+            Facet::License => Url::parse(value).ok().map(FacetValue::Url),
+            _ => None,
+        };
+        typed.unwrap_or_else(|| FacetValue::Text(value.to_string()))
+    }
+}
+
+impl fmt::Display for FacetValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FacetValue::Year(year) => write!(f, "{}", year),
+            FacetValue::Type(ty) => f.write_str(ty.label()),
+            FacetValue::Url(url) => f.write_str(url.as_str()),
+            FacetValue::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+/// The values crossref returned for a facet and how many works have each of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FacetResult {
+    /// the facet the values belong to
+    pub facet: Facet,
+    /// how many distinct values the facet has, crossref may return fewer
+    pub value_count: usize,
+    /// the values and their counts, sorted by descending count
+    pub values: Vec<(FacetValue, usize)>,
+}
+
+impl FacetResult {
+    /// the typed values of the `item` crossref returned for the `facet`
+    ///
+    /// Values with the same count are sorted by their text, so the order does not depend on the response.
+    pub fn new(facet: Facet, item: &FacetItem) -> Self {
+        let mut values: Vec<_> = item
+            .values
+            .iter()
+            .map(|(value, count)| (FacetValue::parse(facet, value), *count))
+            .collect();
+        values.sort_by(|(a, a_count), (b, b_count)| match b_count.cmp(a_count) {
+            Ordering::Equal => a.to_string().cmp(&b.to_string()),
+            ordering => ordering,
+        });
+        FacetResult {
+            facet,
+            value_count: item.value_count,
+            values,
+        }
+    }
+
+    /// iterates over the values and their counts, the most frequent value first
+    pub fn iter(&self) -> impl Iterator<Item = (&FacetValue, usize)> {
+        self.values.iter().map(|(value, count)| (value, *count))
+    }
+
+    /// the count of the `value`, if crossref returned it
+    pub fn count(&self, value: &FacetValue) -> Option<usize> {
+        self.iter()
+            .find(|(v, _)| *v == value)
+            .map(|(_, count)| count)
+    }
+
+    /// the number of values crossref returned
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// whether crossref returned no values
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// the typed result of the `facet` in the facets of a response, if it was requested
+pub(crate) fn facet_result(facets: &FacetMap, facet: Facet) -> Option<FacetResult> {
+    facets
+        .get(facet.as_str())
+        .map(|item| FacetResult::new(facet, item))
+}
+
+/// the typed results of all facets of a response crossref returned, in the order of their names
+pub(crate) fn facet_results(facets: &FacetMap) -> Vec<FacetResult> {
+    let mut results: Vec<_> = facets
+        .iter()
+        .filter_map(|(name, item)| name.parse().ok().map(|facet| FacetResult::new(facet, item)))
+        .collect();
+    results.sort_by(|a, b| a.facet.as_str().cmp(b.facet.as_str()));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_facets() {
+        let facets: FacetMap = serde_json::from_str(
+            r#"{
+      "published": {"value-count": 3, "values": {"2019": 12, "2018": 30, "2020": 12}},
+      "type-name": {"value-count": 2, "values": {"Journal Article": 40, "Posted Content": 2}},
+      "license": {"value-count": 1, "values": {"http://creativecommons.org/licenses/by/4.0/": 7}},
+      "publisher-name": {"value-count": 1, "values": {"Elsevier BV": 20}},
+      "not-a-facet": {"value-count": 1, "values": {"x": 1}}
+    }"#,
+        )
+        .unwrap();
+
+        let published = facet_result(&facets, Facet::Published).unwrap();
+        assert_eq!(3, published.value_count);
+        assert_eq!(
+            vec![
+                (&FacetValue::Year(2018), 30),
+                (&FacetValue::Year(2019), 12),
+                (&FacetValue::Year(2020), 12),
+            ],
+            published.iter().collect::<Vec<_>>()
+        );
+
+        let types = facet_result(&facets, Facet::TypeName).unwrap();
+        assert_eq!(
+            Some(40),
+            types.count(&FacetValue::Type(Type::JournalArticle))
+        );
+        assert_eq!(Some(2), types.count(&FacetValue::Type(Type::PostedContent)));
+
+        let license = facet_result(&facets, Facet::License).unwrap();
+        assert_eq!(
+            FacetValue::Url(Url::parse("http://creativecommons.org/licenses/by/4.0/").unwrap()),
+            license.values[0].0
+        );
+        assert_eq!(
+            FacetValue::Text("Elsevier BV".to_string()),
+            facet_result(&facets, Facet::PublisherName).unwrap().values[0].0
+        );
+        assert!(facet_result(&facets, Facet::ORCID).is_none());
+
+        assert_eq!(
+            vec![
+                Facet::License,
+                Facet::Published,
+                Facet::PublisherName,
+                Facet::TypeName
+            ],
+            facet_results(&facets)
+                .iter()
+                .map(|result| result.facet)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -8,10 +8,13 @@ use serde_json::{from_value, Value};
 use std::collections::HashMap;
 use std::fmt;
 
+/// provides the typed results of the facets of a response
+pub mod facet;
 /// provides the types for a work response
 pub mod work;
 
-pub use crate::response::work::{Work, WorkList};
+pub use crate::response::facet::{FacetResult, FacetValue};
+pub use crate::response::work::{PartialWork, PartialWorkList, Work, WorkList};
 
 /// Represents the whole crossref response for a any request.
 #[derive(Debug, Clone, Serialize)]
@@ -68,10 +71,7 @@ impl Response {
 
     /// checks whether the `message` holds a variant of `RouteNotFound`
     pub fn is_route_not_found(&self) -> bool {
-        match &self.message {
-            Some(Message::RouteNotFound) => true,
-            _ => false,
-        }
+        matches!(&self.message, Some(Message::RouteNotFound))
     }
 }
 
@@ -238,11 +238,11 @@ pub struct CrossrefType {
     pub label: String,
 }
 
-impl Into<CrossrefType> for crate::query::types::Type {
-    fn into(self) -> CrossrefType {
+impl From<crate::query::types::Type> for CrossrefType {
+    fn from(ty: crate::query::types::Type) -> Self {
         CrossrefType {
-            id: self.id().to_string(),
-            label: self.label().to_string(),
+            id: ty.id().to_string(),
+            label: ty.label().to_string(),
         }
     }
 }
@@ -328,6 +328,8 @@ pub struct QueryResponse {
 // TODO impl CrossrefRoute for QueryResponse
 
 /// facets are returned as map
+///
+/// Use [`WorkList::facet`] to read them typed and keyed by [`Facet`] instead.
 pub type FacetMap = HashMap<String, FacetItem>;
 
 /// if a `facet` was set in a request `FacetMap` will be  in a `List` response as additional field of the message
@@ -1,8 +1,10 @@
 // see https://github.com/Crossref/rest-api-doc/blob/master/api_format.md
 
 use crate::error::Result;
+use crate::query::facet::Facet;
+use crate::response::facet::{self, FacetResult};
 use crate::response::{FacetMap, QueryResponse};
-use crate::{Crossref, WorkListQuery, WorksQuery};
+use crate::{WorkListQuery, WorksQuery};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -30,6 +32,40 @@ pub struct WorkList {
     pub next_cursor: Option<String>,
 }
 
+impl WorkList {
+    /// the typed values of the `facet` and their counts, if the facet was requested
+    ///
+    /// # Example
+    ///
+    /// ```edition2018
+    /// # #[cfg(feature = "blocking")] {
+    /// # use crossref::{Crossref, WorksQuery};
+    /// use crossref::query::facet::{Facet, FacetCount};
+    /// # fn run() -> Result<(), crossref::Error> {
+    /// # let client = Crossref::builder().build()?;
+    /// let works = client.works(WorksQuery::new("ontologies").facet(FacetCount {
+    ///     facet: Facet::Published,
+    ///     count: None,
+    /// }))?;
+    /// if let Some(published) = works.facet(Facet::Published) {
+    ///     for (year, count) in published.iter() {
+    ///         println!("{}: {}", year, count);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # }
+    /// ```
+    pub fn facet(&self, facet: Facet) -> Option<FacetResult> {
+        facet::facet_result(&self.facets, facet)
+    }
+
+    /// the typed results of all facets in the response, in the order of their names
+    pub fn facet_results(&self) -> Vec<FacetResult> {
+        facet::facet_results(&self.facets)
+    }
+}
+
 /// the main return type of the crossref api
 /// represents a publication
 /// based on the [crossref rest-api-doc](https://github.com/CrossRef/rest-api-doc/blob/master/api_format.md#work)
@@ -51,7 +87,11 @@ pub struct Work {
     /// Abstract as a JSON string or a JATS XML snippet encoded into a JSON string
     #[serde(rename = "abstract")]
     pub abstract_: Option<String>,
-    /// Count of outbound references deposited with Crossref
+    /// Count of outbound references deposited with Crossref, selected with `reference-count`
+    ///
+    /// The same count as `references_count` under its current name, missing in older responses.
+    pub reference_count: Option<i32>,
+    /// Count of outbound references deposited with Crossref, the deprecated name of `reference_count`
     pub references_count: i32,
     /// Count of inbound references deposited with Crossref
     pub is_referenced_by_count: i32,
@@ -144,6 +184,150 @@ pub struct Work {
     pub review: Option<Relations>,
 }
 
+/// A `WorkList` of `PartialWork` items, the response to a query that selects only some fields
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PartialWorkList {
+    /// if facets where part in the request they are also included in the response
+    #[serde(default)]
+    pub facets: FacetMap,
+    /// the number of items that match the response
+    pub total_results: usize,
+    /// crossref responses for large number of items are divided in pages, number of elements to expect in `items`
+    pub items_per_page: Option<usize>,
+    /// if a query was set in the request, this will also be part in the response
+    pub query: Option<QueryResponse>,
+    /// all work items that are returned
+    pub items: Vec<PartialWork>,
+    /// deep page through `/works` result sets
+    pub next_cursor: Option<String>,
+}
+
+impl PartialWorkList {
+    /// the typed values of the `facet` and their counts, if the facet was requested
+    pub fn facet(&self, facet: Facet) -> Option<FacetResult> {
+        facet::facet_result(&self.facets, facet)
+    }
+
+    /// the typed results of all facets in the response, in the order of their names
+    pub fn facet_results(&self) -> Vec<FacetResult> {
+        facet::facet_results(&self.facets)
+    }
+}
+
+/// A `Work` of which only some fields were returned, because the query selected them with `WorksQuery::select`
+///
+/// Every field is optional, fields that were not selected are `None`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+#[allow(missing_docs)]
+pub struct PartialWork {
+    /// Name of work's publisher
+    pub publisher: Option<String>,
+    /// Work titles, including translated titles
+    pub title: Option<Vec<String>>,
+    /// Work titles in the work's original publication language
+    pub original_title: Option<Vec<String>>,
+    /// the language of this work
+    pub language: Option<String>,
+    pub short_title: Option<Vec<String>>,
+    /// Abstract as a JSON string or a JATS XML snippet encoded into a JSON string
+    #[serde(rename = "abstract")]
+    pub abstract_: Option<String>,
+    /// Count of outbound references deposited with Crossref, selected with `reference-count`
+    ///
+    /// The same count as `references_count` under its current name, missing in older responses.
+    pub reference_count: Option<i32>,
+    /// Count of outbound references deposited with Crossref, the deprecated name of `reference_count`
+    pub references_count: Option<i32>,
+    /// Count of inbound references deposited with Crossref
+    pub is_referenced_by_count: Option<i32>,
+    /// Currently always `Crossref`
+    pub source: Option<String>,
+    pub journal_issue: Option<Issue>,
+    /// DOI prefix identifier of the form `http://id.crossref.org/prefix/DOI_PREFIX`
+    pub prefix: Option<String>,
+    /// DOI of the work
+    #[serde(rename = "DOI")]
+    pub doi: Option<String>,
+    /// URL form of the work's DOI
+    #[serde(rename = "URL")]
+    pub url: Option<String>,
+    /// Member identifier of the form `http://id.crossref.org/member/MEMBER_ID`
+    pub member: Option<String>,
+    /// Enumeration, one of the type ids from `https://api.crossref.org/v1/types`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    /// the day this work entry was created
+    pub created: Option<Date>,
+    /// Date on which the DOI was first registered
+    pub date: Option<Date>,
+    /// Date on which the work metadata was most recently updated
+    pub deposited: Option<Date>,
+    /// the score of the publication if any
+    pub score: Option<f32>,
+    /// Date on which the work metadata was most recently indexed
+    pub indexed: Option<Date>,
+    /// Earliest of `published-print` and `published-online`
+    pub issued: Option<PartialDate>,
+    /// Date on which posted content was made available online
+    pub posted: Option<PartialDate>,
+    /// Date on which a work was accepted, after being submitted, during a submission process
+    pub accepted: Option<PartialDate>,
+    /// Work subtitles, including original language and translated
+    pub subtitle: Option<Vec<String>>,
+    /// Full titles of the containing work (usually a book or journal)
+    pub container_title: Option<Vec<String>>,
+    /// Abbreviated titles of the containing work
+    pub short_container_title: Option<Vec<String>>,
+    /// Group title for posted content
+    pub group_title: Option<String>,
+    /// Issue number of an article's journal
+    pub issue: Option<String>,
+    /// Volume number of an article's journal
+    pub volume: Option<String>,
+    /// Pages numbers of an article within its journal
+    pub page: Option<String>,
+    /// the number of the corresponding article
+    pub article_number: Option<String>,
+    /// Date on which the work was published in print
+    pub published_print: Option<PartialDate>,
+    /// Date on which the work was published online
+    pub published_online: Option<PartialDate>,
+    /// Subject category names, a controlled vocabulary from Sci-Val.
+    pub subject: Option<Vec<String>>,
+    #[serde(rename = "ISSN")]
+    pub issn: Option<Vec<String>>,
+    /// List of ISSNs with ISSN type information
+    pub issn_type: Option<Vec<ISSN>>,
+    #[serde(rename = "ISBN")]
+    pub isbn: Option<Vec<String>>,
+    pub archive: Option<Vec<String>>,
+    pub license: Option<Vec<License>>,
+    pub funder: Option<Vec<FundingBody>>,
+    pub assertion: Option<Vec<Assertion>>,
+    pub author: Option<Vec<Contributor>>,
+    pub editor: Option<Vec<Contributor>>,
+    pub chair: Option<Vec<Contributor>>,
+    pub translator: Option<Vec<Contributor>>,
+    pub update_to: Option<Vec<Update>>,
+    /// Link to an update policy covering Crossmark updates for this work
+    pub update_policy: Option<String>,
+    /// URLs to full-text locations
+    pub link: Option<Vec<ResourceLink>>,
+    pub clinical_trial_number: Option<Vec<ClinicalTrialNumber>>,
+    /// Other identifiers for the work provided by the depositing member
+    pub alternative_id: Option<Vec<String>>,
+    /// List of references made by the work
+    pub reference: Option<Vec<Reference>>,
+    /// Information on domains that support Crossmark for this work
+    pub content_domain: Option<ContentDomain>,
+    /// Relations to other works
+    pub relation: Option<Relations>,
+    /// Peer review metadata
+    pub review: Option<Relations>,
+}
+
 /// Helper struct to represent dates in the cross ref api as nested arrays of numbers
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct DateParts(pub Vec<Vec<Option<u32>>>);
@@ -157,9 +341,9 @@ impl DateParts {
         fn naive(v: &[Option<u32>]) -> Option<NaiveDate> {
             match v.len() {
                 0 => None,
-                1 => Some(NaiveDate::from_ymd(v[0]? as i32, 0, 0)),
-                2 => Some(NaiveDate::from_ymd(v[0]? as i32, v[1]?, 0)),
-                3 => Some(NaiveDate::from_ymd(v[0]? as i32, v[1]?, v[2]?)),
+                1 => NaiveDate::from_ymd_opt(v[0]? as i32, 1, 1),
+                2 => NaiveDate::from_ymd_opt(v[0]? as i32, v[1]?, 1),
+                3 => NaiveDate::from_ymd_opt(v[0]? as i32, v[1]?, v[2]?),
                 _ => None,
             }
         }
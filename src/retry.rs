@@ -0,0 +1,258 @@
+use crate::error::{Error, ErrorKind};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+/// Determines whether and when a failed request is attempted again.
+///
+/// A request is retried if it timed out, the connection failed or the server answered with one of the `statuses`.
+/// The delay before the `n`th retry is `base_delay * 2^(n - 1)`, capped at `max_delay`, plus a random jitter.
+/// If the server sends a `Retry-After` header, its value is used instead of the exponential delay,
+/// but never longer than `max_delay`.
+///
+/// # Example
+///
+/// ```edition2018
+/// # #[cfg(feature = "blocking")] {
+/// use crossref::{Crossref, RetryPolicy};
+/// use std::time::Duration;
+/// # fn run() -> Result<(), crossref::Error> {
+/// let client = Crossref::builder()
+///     .retry(
+///         RetryPolicy::default()
+///             .max_attempts(5)
+///             .base_delay(Duration::from_secs(1)),
+///     )
+///     .build()?;
+/// # Ok(())
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// how many attempts are made in total, including the first one
+    pub max_attempts: usize,
+    /// the delay before the first retry, doubled for each following retry
+    pub base_delay: Duration,
+    /// upper bound for the exponential delay and the `Retry-After` value
+    pub max_delay: Duration,
+    /// upper bound of the random delay that is added on top of the exponential delay
+    pub jitter: Duration,
+    /// the response status codes that are retried
+    pub statuses: Vec<u16>,
+    /// whether to wait as long as the `Retry-After` header of a response demands, up to `max_delay`
+    pub respect_retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// the status codes that are retried by default: `429 Too Many Requests` and transient server errors
+    pub const DEFAULT_STATUSES: [u16; 5] = [429, 500, 502, 503, 504];
+
+    /// a policy that makes only a single attempt and retries no status
+    ///
+    /// A failed response is handled like any other response, without a `RetriesExhausted` error.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            statuses: Vec::new(),
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// set the number of attempts, including the first one
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// set the delay before the first retry
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// set the upper bound for the exponential delay and the `Retry-After` value
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// set the upper bound for the random delay added to each retry
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// replace the status codes that are retried
+    pub fn statuses(mut self, statuses: Vec<u16>) -> Self {
+        self.statuses = statuses;
+        self
+    }
+
+    /// additionally retry responses with the `status` code
+    pub fn retry_status(mut self, status: u16) -> Self {
+        if !self.statuses.contains(&status) {
+            self.statuses.push(status);
+        }
+        self
+    }
+
+    /// whether to wait as long as the `Retry-After` header of a response demands, up to `max_delay`
+    pub fn respect_retry_after(mut self, respect_retry_after: bool) -> Self {
+        self.respect_retry_after = respect_retry_after;
+        self
+    }
+
+    /// whether another attempt is allowed after `attempt` attempts were made
+    pub(crate) fn has_attempts_left(&self, attempt: usize) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// whether a response with the `status` should be retried
+    pub(crate) fn retries_status(&self, status: u16) -> bool {
+        self.statuses.contains(&status)
+    }
+
+    /// whether a request that failed with the `error` should be retried
+    pub(crate) fn retries_error(error: &Error) -> bool {
+        match error.kind() {
+            ErrorKind::ReqWest { reqwest } => {
+                reqwest.is_timeout()
+                    || reqwest.is_connect()
+                    || reqwest.is_request()
+                    || reqwest.is_body()
+            }
+            _ => false,
+        }
+    }
+
+    /// the time to wait before the next attempt, after `attempt` attempts failed
+    pub(crate) fn delay(&self, attempt: usize, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after.filter(|_| self.respect_retry_after) {
+            return retry_after.min(self.max_delay);
+        }
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        let backoff = self
+            .base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        backoff + self.random_jitter()
+    }
+
+    /// a random duration between zero and `jitter`
+    fn random_jitter(&self) -> Duration {
+        let max = self.jitter.as_millis() as u64;
+        if max == 0 {
+            Duration::from_millis(0)
+        } else {
+            Duration::from_millis(rand::thread_rng().gen_range(0..=max))
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: Duration::from_millis(250),
+            statuses: RetryPolicy::DEFAULT_STATUSES.to_vec(),
+            respect_retry_after: true,
+        }
+    }
+}
+
+/// reads the `Retry-After` header, which is either a number of seconds or a http date
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(Utc::now())
+            .to_std()
+            .unwrap_or_else(|_| Duration::from_secs(0)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn exponential_delay() {
+        let policy = RetryPolicy::default()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(350))
+            .jitter(Duration::from_millis(0));
+
+        assert_eq!(Duration::from_millis(100), policy.delay(1, None));
+        assert_eq!(Duration::from_millis(200), policy.delay(2, None));
+        assert_eq!(Duration::from_millis(350), policy.delay(3, None));
+        assert_eq!(Duration::from_millis(350), policy.delay(64, None));
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let policy = RetryPolicy::default()
+            .base_delay(Duration::from_millis(100))
+            .jitter(Duration::from_millis(50));
+        for _ in 0..100 {
+            let delay = policy.delay(1, None);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn retry_after_overrides_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            Duration::from_secs(7),
+            policy.delay(1, Some(Duration::from_secs(7)))
+        );
+        // a server can't stall the client for longer than the policy allows
+        assert_eq!(
+            policy.max_delay,
+            policy.delay(1, Some(Duration::from_secs(24 * 60 * 60)))
+        );
+        let policy = policy
+            .respect_retry_after(false)
+            .jitter(Duration::from_millis(0));
+        assert_eq!(
+            policy.base_delay,
+            policy.delay(1, Some(Duration::from_secs(7)))
+        );
+    }
+
+    #[test]
+    fn parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(None, retry_after(&headers));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(Some(Duration::from_secs(120)), retry_after(&headers));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(Some(Duration::from_secs(0)), retry_after(&headers));
+    }
+
+    #[test]
+    fn retried_statuses() {
+        assert!(!RetryPolicy::none().retries_status(429));
+        let policy = RetryPolicy::none().retry_status(418);
+        assert!(!policy.retries_status(429));
+        assert!(policy.retries_status(418));
+        assert!(!policy.retries_status(404));
+        assert!(!policy.has_attempts_left(1));
+        assert!(policy.max_attempts(2).has_attempts_left(1));
+    }
+}
@@ -0,0 +1,518 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::response::Work;
+use flate2::read::{GzDecoder, MultiGzDecoder};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// the size of the chunks the files of a snapshot are read in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A public data file or Metadata Plus snapshot on the local disk
+///
+/// A snapshot consists of json files shaped like `WorkList` messages, `{"items": [...]}`, either directly or
+/// wrapped in a `message`. A snapshot can be
+///
+/// * a directory of `.json` and `.json.gz` files, like the public data files
+/// * a `.tar`, `.tar.gz` or `.tgz` archive of such files, like the Metadata Plus snapshots
+/// * a single `.json` or `.json.gz` file
+///
+/// The files are read as a stream and only one record is held in memory at a time.
+/// A record that is not a valid `Work` is reported as a `MalformedRecord` error and the reading continues
+/// with the next record. An error while reading a file skips the rest of the file, an error while reading
+/// the archive itself ends the iteration.
+///
+/// This requires the `snapshot` feature.
+///
+/// # Example
+///
+/// ```edition2018
+/// use crossref::{ErrorKind, Snapshot};
+/// # fn run() -> Result<(), crossref::Error> {
+/// let mut snapshot = Snapshot::open("all.json.tar.gz")?;
+///
+/// for work in snapshot.works()? {
+///     match work {
+///         Ok(work) => println!("{}", work.doi),
+///         Err(err) => match err.kind() {
+///             ErrorKind::MalformedRecord { .. } => eprintln!("skipped {}", err),
+///             _ => return Err(err),
+///         },
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Snapshot {
+    /// where the snapshot is
+    path: PathBuf,
+    /// how the snapshot is laid out
+    layout: Layout,
+}
+
+/// the name and the content of each file of a snapshot
+type Files<'a> = Box<dyn Iterator<Item = io::Result<(String, Box<dyn Read + 'a>)>> + 'a>;
+
+/// how the files of a `Snapshot` are stored
+enum Layout {
+    /// the files in a directory or a single file
+    Files(Vec<PathBuf>),
+    /// the files in a tar archive
+    Archive(tar::Archive<Box<dyn Read>>),
+}
+
+impl Snapshot {
+    /// opens the snapshot at `path`
+    ///
+    /// # Errors
+    ///
+    /// This fails if the path cannot be read or is a file that is neither json nor a tar archive.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let layout = if path.is_dir() {
+            let mut files = Vec::new();
+            for entry in fs::read_dir(&path)? {
+                let file = entry?.path();
+                if file.is_file() && is_json(&file.to_string_lossy()) {
+                    files.push(file);
+                }
+            }
+            // the files of the public data files are numbered
+            files.sort();
+            Layout::Files(files)
+        } else {
+            let name = path.to_string_lossy().to_lowercase();
+            if name.ends_with(".tar") {
+                Layout::Archive(tar::Archive::new(Box::new(File::open(&path)?)))
+            } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+                let file = GzDecoder::new(File::open(&path)?);
+                Layout::Archive(tar::Archive::new(Box::new(file)))
+            } else if is_json(&name) {
+                Layout::Files(vec![path.clone()])
+            } else {
+                return Err(ErrorKind::Config {
+                    msg: format!(
+                        "`{}` is neither a directory, a json file nor a tar archive",
+                        path.display()
+                    ),
+                }
+                .into());
+            }
+        };
+        Ok(Snapshot { path, layout })
+    }
+
+    /// where the snapshot is
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// an iterator over all works of the snapshot
+    ///
+    /// The works of an archive can be iterated only once.
+    pub fn works(&mut self) -> Result<SnapshotWorks<'_>> {
+        let files: Files<'_> = match &mut self.layout {
+            Layout::Files(files) => Box::new(files.iter().map(|path| {
+                let name = path.to_string_lossy().into_owned();
+                let file = File::open(path)?;
+                Ok((name.clone(), decode(&name, file)))
+            })),
+            Layout::Archive(archive) => Box::new(archive.entries()?.filter_map(|entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(err) => return Some(Err(err)),
+                };
+                let name = entry.path().ok()?.to_string_lossy().into_owned();
+                if entry.header().entry_type().is_file() && is_json(&name) {
+                    Some(Ok((name.clone(), decode(&name, entry))))
+                } else {
+                    None
+                }
+            })),
+        };
+        Ok(SnapshotWorks {
+            files,
+            current: None,
+            failed: false,
+        })
+    }
+}
+
+/// An iterator over the works of a `Snapshot`
+///
+/// Created by [`Snapshot::works`].
+pub struct SnapshotWorks<'a> {
+    /// the name and content of each file of the snapshot
+    files: Files<'a>,
+    /// the name and the records of the file that is read
+    current: Option<(String, Records<Box<dyn Read + 'a>>)>,
+    /// whether reading the snapshot failed
+    failed: bool,
+}
+
+impl<'a> Iterator for SnapshotWorks<'a> {
+    type Item = Result<Work>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.failed {
+                return None;
+            }
+            let (name, records) = match &mut self.current {
+                Some(current) => current,
+                None => match self.files.next()? {
+                    Ok((name, file)) => self.current.get_or_insert((name, Records::new(file))),
+                    Err(err) => {
+                        self.failed = true;
+                        return Some(Err(err.into()));
+                    }
+                },
+            };
+            let malformed = |record: usize, msg: String| -> Error {
+                ErrorKind::MalformedRecord {
+                    file: name.clone(),
+                    record,
+                    msg,
+                }
+                .into()
+            };
+            match records.next() {
+                Ok(Some(Record::Item(item))) => {
+                    let record = records.count;
+                    return Some(
+                        serde_json::from_slice(&item)
+                            .map_err(|err| malformed(record, err.to_string())),
+                    );
+                }
+                Ok(Some(Record::Malformed(msg))) => {
+                    let err = malformed(records.count + 1, msg);
+                    self.current = None;
+                    return Some(Err(err));
+                }
+                Ok(None) => self.current = None,
+                Err(err) => {
+                    // the content of a file can be corrupt, the next file is still read
+                    self.current = None;
+                    return Some(Err(err.into()));
+                }
+            }
+        }
+    }
+}
+
+/// whether the file `name` is a json file, possibly compressed
+fn is_json(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with(".json") || name.ends_with(".json.gz")
+}
+
+/// decompresses the `file` if it is compressed
+fn decode<'a, R: Read + 'a>(name: &str, file: R) -> Box<dyn Read + 'a> {
+    if name.to_lowercase().ends_with(".gz") {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    }
+}
+
+/// what the `Records` of a file yield
+#[derive(Debug, PartialEq, Eq)]
+enum Record {
+    /// the json of an item of the `items` array
+    Item(Vec<u8>),
+    /// the file has no complete `items` array, no more records follow
+    Malformed(String),
+}
+
+/// where in the file the `Records` are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// looking for the `items` array
+    Seeking,
+    /// between the items of the `items` array
+    Items,
+    /// after the `items` array
+    Done,
+}
+
+/// Splits the `items` array of a json file into the json of its items, without parsing the file
+struct Records<R> {
+    reader: R,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+    state: State,
+    /// the number of items returned so far
+    count: usize,
+}
+
+impl<R: Read> Records<R> {
+    fn new(reader: R) -> Self {
+        Records {
+            reader,
+            buf: vec![0; CHUNK_SIZE],
+            pos: 0,
+            len: 0,
+            state: State::Seeking,
+            count: 0,
+        }
+    }
+
+    /// the next byte without consuming it
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        if self.pos == self.len {
+            self.len = loop {
+                match self.reader.read(&mut self.buf) {
+                    Ok(len) => break len,
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                }
+            };
+            self.pos = 0;
+            if self.len == 0 {
+                return Ok(None);
+            }
+        }
+        Ok(Some(self.buf[self.pos]))
+    }
+
+    fn byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.peek()?;
+        if byte.is_some() {
+            self.pos += 1;
+        }
+        Ok(byte)
+    }
+
+    /// consumes the rest of a string after its opening quote, appending it to `out`
+    fn string(&mut self, out: &mut Vec<u8>) -> io::Result<bool> {
+        while let Some(byte) = self.byte()? {
+            out.push(byte);
+            match byte {
+                b'\\' => match self.byte()? {
+                    Some(escaped) => out.push(escaped),
+                    None => return Ok(false),
+                },
+                b'"' => return Ok(true),
+                _ => {}
+            }
+        }
+        Ok(false)
+    }
+
+    /// consumes whitespace and the `skip` bytes
+    fn skip(&mut self, skip: &[u8]) -> io::Result<Option<u8>> {
+        while let Some(byte) = self.peek()? {
+            if byte.is_ascii_whitespace() || skip.contains(&byte) {
+                self.pos += 1;
+            } else {
+                return Ok(Some(byte));
+            }
+        }
+        Ok(None)
+    }
+
+    /// moves to the first item of the `items` array of the file or its `message`
+    fn seek_items(&mut self) -> io::Result<bool> {
+        let mut depth = 0;
+        let mut key = Vec::new();
+        while let Some(byte) = self.byte()? {
+            match byte {
+                b'"' => {
+                    key.clear();
+                    if !self.string(&mut key)? {
+                        return Ok(false);
+                    }
+                    // the key ends with its closing quote
+                    if depth <= 2 && key == b"items\"" && self.skip(b"")? == Some(b':') {
+                        self.pos += 1;
+                        if self.skip(b"")? == Some(b'[') {
+                            self.pos += 1;
+                            return Ok(true);
+                        }
+                    }
+                }
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(false)
+    }
+
+    /// the next item of the `items` array, `None` after the last one
+    fn next(&mut self) -> io::Result<Option<Record>> {
+        match self.state {
+            State::Done => return Ok(None),
+            State::Seeking => {
+                if !self.seek_items()? {
+                    self.state = State::Done;
+                    return Ok(Some(Record::Malformed(
+                        "the file has no `items` array".to_string(),
+                    )));
+                }
+                self.state = State::Items;
+            }
+            State::Items => {}
+        }
+
+        match self.skip(b",")? {
+            Some(b']') => {
+                self.state = State::Done;
+                return Ok(None);
+            }
+            Some(_) => {}
+            None => {
+                self.state = State::Done;
+                return Ok(Some(Record::Malformed(
+                    "the file ends within the `items` array".to_string(),
+                )));
+            }
+        }
+
+        let mut item = Vec::new();
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                Some(b',') | Some(b']') if depth == 0 => break,
+                None => {
+                    self.state = State::Done;
+                    return Ok(Some(Record::Malformed(
+                        "the file ends within an item".to_string(),
+                    )));
+                }
+                Some(_) => {}
+            }
+            let byte = self.byte()?.unwrap_or_default();
+            item.push(byte);
+            match byte {
+                b'"' => {
+                    self.string(&mut item)?;
+                }
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        while item.last().is_some_and(u8::is_ascii_whitespace) {
+            item.pop();
+        }
+        self.count += 1;
+        Ok(Some(Record::Item(item)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::tests::work_json;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn records(json: &str) -> Vec<Record> {
+        // a tiny chunk size splits the items across reads
+        let mut records = Records::new(json.as_bytes());
+        records.buf = vec![0; 3];
+        std::iter::from_fn(|| records.next().unwrap()).collect()
+    }
+
+    fn gz(content: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn splits_items() {
+        assert_eq!(
+            vec![
+                Record::Item(br#"{"a":"]}\""}"#.to_vec()),
+                Record::Item(br#"[1, {"b":2}]"#.to_vec()),
+            ],
+            records(r#"{"message":{"facets":{"items":1},"items": [{"a":"]}\""}, [1, {"b":2}] ]}}"#)
+        );
+        assert_eq!(
+            vec![Record::Malformed(
+                "the file has no `items` array".to_string()
+            )],
+            records(r#"{"message":{}}"#)
+        );
+        assert_eq!(
+            vec![
+                Record::Item(b"{}".to_vec()),
+                Record::Malformed("the file ends within an item".to_string())
+            ],
+            records(r#"{"items":[{}, {"a":1"#)
+        );
+    }
+
+    #[test]
+    fn reads_tar_gz_archives() {
+        let items = format!(
+            r#"{{"items":[{},{{"DOI":"10.5555/broken"}},{}]}}"#,
+            work_json("10.5555/1"),
+            work_json("10.5555/2")
+        );
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, content) in [
+            ("snapshot/0.json", items.into_bytes()),
+            (
+                "snapshot/1.json.gz",
+                gz(&format!(r#"{{"items":[{}]}}"#, work_json("10.5555/3"))),
+            ),
+            ("snapshot/README.md", b"not json".to_vec()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            archive
+                .append_data(&mut header, name, content.as_slice())
+                .unwrap();
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("all.json.tar.gz");
+        fs::write(&path, archive.into_inner().unwrap().finish().unwrap()).unwrap();
+
+        let mut snapshot = Snapshot::open(&path).unwrap();
+        let works: Vec<_> = snapshot.works().unwrap().collect();
+        assert_eq!(4, works.len());
+        match works[1].as_ref().unwrap_err().kind() {
+            ErrorKind::MalformedRecord { file, record, .. } => {
+                assert_eq!("snapshot/0.json", file);
+                assert_eq!(2, *record);
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        let dois: Vec<_> = works
+            .into_iter()
+            .filter_map(|work| work.ok())
+            .map(|work| work.doi)
+            .collect();
+        assert_eq!(vec!["10.5555/1", "10.5555/2", "10.5555/3"], dois);
+    }
+
+    #[test]
+    fn reads_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("1.json.gz"),
+            gz(&format!(r#"{{"items":[{}]}}"#, work_json("10.5555/2"))),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("0.json"),
+            format!(r#"{{"items":[{}]}}"#, work_json("10.5555/1")),
+        )
+        .unwrap();
+
+        let dois: Vec<_> = Snapshot::open(dir.path())
+            .unwrap()
+            .works()
+            .unwrap()
+            .map(|work| work.unwrap().doi)
+            .collect();
+        assert_eq!(vec!["10.5555/1", "10.5555/2"], dois);
+        assert!(Snapshot::open(dir.path().join("0.txt")).is_err());
+    }
+}
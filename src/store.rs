@@ -0,0 +1,583 @@
+use crate::error::Result;
+use crate::response::{Funder, Journal, Member, Work};
+use rusqlite::{params, Connection, OptionalExtension, ToSql, Transaction};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// the tables of the store, every statement can be executed again on an existing database
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS works (
+    doi TEXT PRIMARY KEY,
+    title TEXT,
+    type TEXT NOT NULL,
+    publisher TEXT NOT NULL,
+    member TEXT NOT NULL,
+    indexed INTEGER NOT NULL,
+    deposited INTEGER,
+    json TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS work_issns (
+    doi TEXT NOT NULL,
+    issn TEXT NOT NULL,
+    type TEXT,
+    PRIMARY KEY (doi, issn)
+);
+CREATE INDEX IF NOT EXISTS work_issns_issn ON work_issns (issn);
+CREATE TABLE IF NOT EXISTS authors (
+    doi TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    given TEXT,
+    family TEXT NOT NULL,
+    orcid TEXT,
+    PRIMARY KEY (doi, position)
+);
+CREATE INDEX IF NOT EXISTS authors_orcid ON authors (orcid);
+CREATE TABLE IF NOT EXISTS work_references (
+    doi TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    reference_doi TEXT,
+    unstructured TEXT,
+    PRIMARY KEY (doi, position)
+);
+CREATE INDEX IF NOT EXISTS work_references_reference_doi ON work_references (reference_doi);
+CREATE TABLE IF NOT EXISTS work_funders (
+    doi TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    funder_doi TEXT,
+    awards TEXT NOT NULL,
+    PRIMARY KEY (doi, position)
+);
+CREATE INDEX IF NOT EXISTS work_funders_funder_doi ON work_funders (funder_doi);
+CREATE TABLE IF NOT EXISTS licenses (
+    doi TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    content_version TEXT NOT NULL,
+    delay_in_days INTEGER NOT NULL,
+    PRIMARY KEY (doi, position)
+);
+CREATE TABLE IF NOT EXISTS funders (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    location TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    json TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS members (
+    id INTEGER PRIMARY KEY,
+    primary_name TEXT NOT NULL,
+    location TEXT NOT NULL,
+    json TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS journals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT,
+    publisher TEXT,
+    json TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS journal_issns (
+    issn TEXT PRIMARY KEY,
+    journal_id INTEGER NOT NULL
+);
+"#;
+
+/// the tables with one row per item of a list of a work
+const WORK_TABLES: [&str; 5] = [
+    "work_issns",
+    "authors",
+    "work_references",
+    "work_funders",
+    "licenses",
+];
+
+/// Saves `Work`, `Funder`, `Member` and `Journal` records in an embedded SQLite database
+///
+/// Each record is kept as json, so it can be read back completely. The authors, references, funders,
+/// licenses and ISSNs of a work are additionally kept in their own tables, which can be queried with
+/// plain SQL and back the lookups of the store.
+///
+/// Works are upserted by their DOI. A work only replaces the stored one if it was indexed at the same
+/// time or later, so saving an older copy of a work, for example from an older snapshot, keeps the newer one.
+///
+/// DOIs, ISSNs and ORCIDs are normalized, so `https://doi.org/10.5555/ABC` finds the work saved as `10.5555/abc`
+/// and `0000-0002-0270-1711` finds authors with the ORCID `http://orcid.org/0000-0002-0270-1711`.
+///
+/// This requires the `store` feature.
+///
+/// # Example
+///
+/// ```edition2018
+/// use crossref::{Crossref, Store, WorksQuery};
+/// # fn run() -> Result<(), crossref::Error> {
+/// let client = Crossref::builder().build()?;
+/// let mut store = Store::open("works.sqlite")?;
+///
+/// let works = client.works(WorksQuery::new("Machine Learning"))?;
+/// store.save_works(&works.items)?;
+///
+/// let work = store.work("10.1037/0003-066x.59.1.29")?;
+/// let by_orcid = store.works_by_orcid("0000-0002-0270-1711")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Store {
+    /// the connection to the database
+    conn: Connection,
+}
+
+impl Store {
+    /// opens the database at `path`, creating it and its tables if necessary
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Store::with_connection(Connection::open(path)?)
+    }
+
+    /// creates a new store in memory, which is gone once it is dropped
+    pub fn in_memory() -> Result<Self> {
+        Store::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// creates the tables in the database of the `conn`, if necessary
+    pub fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// the connection to the database, to query the tables directly
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// saves the `work`, returns `false` if a work that was indexed later is already stored
+    pub fn save_work(&mut self, work: &Work) -> Result<bool> {
+        let tx = self.conn.transaction()?;
+        let saved = upsert_work(&tx, work)?;
+        tx.commit()?;
+        Ok(saved)
+    }
+
+    /// saves all `works` in a single transaction, returns how many replaced the stored ones
+    pub fn save_works<'a, I: IntoIterator<Item = &'a Work>>(&mut self, works: I) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut saved = 0;
+        for work in works {
+            if upsert_work(&tx, work)? {
+                saved += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(saved)
+    }
+
+    /// saves the `funder`, replacing the stored funder with the same id
+    pub fn save_funder(&mut self, funder: &Funder) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO funders (id, name, location, uri, json) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                funder.id,
+                funder.name,
+                funder.location,
+                funder.uri,
+                serde_json::to_string(funder)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// saves the `member`, replacing the stored member with the same id
+    pub fn save_member(&mut self, member: &Member) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO members (id, primary_name, location, json) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                member.id as i64,
+                member.primary_name,
+                member.location,
+                serde_json::to_string(member)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// saves the `journal`, replacing the stored journal that shares one of its ISSNs
+    pub fn save_journal(&mut self, journal: &Journal) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let json = serde_json::to_string(journal)?;
+        let mut id = None;
+        for issn in &journal.issn {
+            id = tx
+                .query_row(
+                    "SELECT journal_id FROM journal_issns WHERE issn = ?1",
+                    params![normalize_issn(issn)],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()?;
+            if id.is_some() {
+                break;
+            }
+        }
+        let id = match id {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE journals SET title = ?2, publisher = ?3, json = ?4 WHERE id = ?1",
+                    params![id, journal.title, journal.publisher, json],
+                )?;
+                tx.execute(
+                    "DELETE FROM journal_issns WHERE journal_id = ?1",
+                    params![id],
+                )?;
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO journals (title, publisher, json) VALUES (?1, ?2, ?3)",
+                    params![journal.title, journal.publisher, json],
+                )?;
+                tx.last_insert_rowid()
+            }
+        };
+        for issn in &journal.issn {
+            tx.execute(
+                "INSERT OR REPLACE INTO journal_issns (issn, journal_id) VALUES (?1, ?2)",
+                params![normalize_issn(issn), id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// the work with the `doi`
+    pub fn work(&self, doi: &str) -> Result<Option<Work>> {
+        self.find_one("SELECT json FROM works WHERE doi = ?1", normalize_doi(doi))
+    }
+
+    /// all works published in the journal with the `issn`
+    pub fn works_by_issn(&self, issn: &str) -> Result<Vec<Work>> {
+        self.find_all(
+            "SELECT works.json FROM works JOIN work_issns ON work_issns.doi = works.doi \
+             WHERE work_issns.issn = ?1 ORDER BY works.doi",
+            normalize_issn(issn),
+        )
+    }
+
+    /// all works with an author with the `orcid`
+    pub fn works_by_orcid(&self, orcid: &str) -> Result<Vec<Work>> {
+        self.find_all(
+            "SELECT DISTINCT works.json, works.doi FROM works JOIN authors ON authors.doi = works.doi \
+             WHERE authors.orcid = ?1 ORDER BY works.doi",
+            normalize_orcid(orcid),
+        )
+    }
+
+    /// all works funded by the funder with the `funder_doi`, like `10.13039/100000001`
+    pub fn works_by_funder(&self, funder_doi: &str) -> Result<Vec<Work>> {
+        self.find_all(
+            "SELECT DISTINCT works.json, works.doi FROM works JOIN work_funders ON work_funders.doi = works.doi \
+             WHERE work_funders.funder_doi = ?1 ORDER BY works.doi",
+            normalize_doi(funder_doi),
+        )
+    }
+
+    /// the funder with the `id`
+    pub fn funder(&self, id: &str) -> Result<Option<Funder>> {
+        self.find_one("SELECT json FROM funders WHERE id = ?1", id)
+    }
+
+    /// the member with the `id`
+    pub fn member(&self, id: usize) -> Result<Option<Member>> {
+        self.find_one("SELECT json FROM members WHERE id = ?1", id as i64)
+    }
+
+    /// the journal with the `issn`
+    pub fn journal(&self, issn: &str) -> Result<Option<Journal>> {
+        self.find_one(
+            "SELECT journals.json FROM journals JOIN journal_issns ON journal_issns.journal_id = journals.id \
+             WHERE journal_issns.issn = ?1",
+            normalize_issn(issn),
+        )
+    }
+
+    /// the record in the json column of the row the `sql` selects
+    fn find_one<T: DeserializeOwned, P: ToSql>(&self, sql: &str, param: P) -> Result<Option<T>> {
+        let json = self
+            .conn
+            .query_row(sql, params![param], |row| row.get::<_, String>(0))
+            .optional()?;
+        match json {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// the records in the json column of all rows the `sql` selects
+    fn find_all<T: DeserializeOwned, P: ToSql>(&self, sql: &str, param: P) -> Result<Vec<T>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(params![param], |row| row.get::<_, String>(0))?;
+        let mut items = Vec::new();
+        for json in rows {
+            items.push(serde_json::from_str(&json?)?);
+        }
+        Ok(items)
+    }
+}
+
+/// inserts or replaces the `work` and the rows of its lists, unless a later indexed one is stored
+fn upsert_work(tx: &Transaction<'_>, work: &Work) -> Result<bool> {
+    let doi = normalize_doi(&work.doi);
+    let changed = tx.execute(
+        "INSERT INTO works (doi, title, type, publisher, member, indexed, deposited, json) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+         ON CONFLICT (doi) DO UPDATE SET title = excluded.title, type = excluded.type, \
+         publisher = excluded.publisher, member = excluded.member, indexed = excluded.indexed, \
+         deposited = excluded.deposited, json = excluded.json \
+         WHERE excluded.indexed >= works.indexed",
+        params![
+            doi,
+            work.title.first(),
+            work.type_,
+            work.publisher,
+            work.member,
+            work.indexed.timestamp as i64,
+            work.deposited.as_ref().map(|date| date.timestamp as i64),
+            serde_json::to_string(work)?
+        ],
+    )?;
+    if changed == 0 {
+        return Ok(false);
+    }
+
+    for table in WORK_TABLES.iter() {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE doi = ?1", table),
+            params![doi],
+        )?;
+    }
+
+    let issns = work
+        .issn_type
+        .iter()
+        .flatten()
+        .map(|issn| (&issn.value, Some(&issn.type_)));
+    let untyped = work.issn.iter().flatten().map(|issn| (issn, None));
+    for (issn, type_) in issns.chain(untyped) {
+        // the typed ISSNs come first and are kept over the same untyped ones
+        tx.execute(
+            "INSERT OR IGNORE INTO work_issns (doi, issn, type) VALUES (?1, ?2, ?3)",
+            params![doi, normalize_issn(issn), type_],
+        )?;
+    }
+    for (position, author) in work.author.iter().flatten().enumerate() {
+        tx.execute(
+            "INSERT INTO authors (doi, position, given, family, orcid) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                doi,
+                position as i64,
+                author.given,
+                author.family,
+                author.orcid.as_ref().map(|orcid| normalize_orcid(orcid))
+            ],
+        )?;
+    }
+    for (position, reference) in work.reference.iter().flatten().enumerate() {
+        tx.execute(
+            "INSERT INTO work_references (doi, position, key, reference_doi, unstructured) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                doi,
+                position as i64,
+                reference.key,
+                reference.doi.as_ref().map(|doi| normalize_doi(doi)),
+                reference.unstructured
+            ],
+        )?;
+    }
+    for (position, funder) in work.funder.iter().flatten().enumerate() {
+        tx.execute(
+            "INSERT INTO work_funders (doi, position, name, funder_doi, awards) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                doi,
+                position as i64,
+                funder.name,
+                funder.doi.as_ref().map(|doi| normalize_doi(doi)),
+                serde_json::to_string(funder.award.as_deref().unwrap_or(&[]))?
+            ],
+        )?;
+    }
+    for (position, license) in work.license.iter().flatten().enumerate() {
+        tx.execute(
+            "INSERT INTO licenses (doi, position, url, content_version, delay_in_days) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                doi,
+                position as i64,
+                license.url,
+                license.content_version,
+                license.delay_in_days
+            ],
+        )?;
+    }
+    Ok(true)
+}
+
+/// the lowercase DOI without a resolver prefix like `https://doi.org/`
+fn normalize_doi(doi: &str) -> String {
+    let doi = doi.trim().to_lowercase();
+    for prefix in &[
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+        "doi:",
+    ] {
+        if let Some(doi) = doi.strip_prefix(prefix) {
+            return doi.to_string();
+        }
+    }
+    doi
+}
+
+/// the uppercase ISSN with a hyphen, like `0003-066X`
+fn normalize_issn(issn: &str) -> String {
+    let issn: String = issn
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    if issn.len() == 8 {
+        format!("{}-{}", &issn[..4], &issn[4..])
+    } else {
+        issn
+    }
+}
+
+/// the bare ORCID iD without `http://orcid.org/`
+fn normalize_orcid(orcid: &str) -> String {
+    orcid
+        .trim()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or("")
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::work::{Contributor, FundingBody, ISSN};
+    use crate::transport::tests::work_json;
+
+    fn work(doi: &str, indexed: usize) -> Work {
+        let mut work: Work = serde_json::from_str(&work_json(doi)).unwrap();
+        work.indexed.timestamp = indexed;
+        work.issn = Some(vec!["0003066x".to_string()]);
+        work.issn_type = Some(vec![ISSN {
+            value: "0003-066X".to_string(),
+            type_: "print".to_string(),
+        }]);
+        work.author = Some(vec![Contributor {
+            family: "Doe".to_string(),
+            given: Some("Jane".to_string()),
+            orcid: Some("http://orcid.org/0000-0002-0270-1711".to_string()),
+            authenticated_orcid: None,
+            affiliation: None,
+        }]);
+        work.funder = Some(vec![FundingBody {
+            name: "National Science Foundation".to_string(),
+            doi: Some("10.13039/100000001".to_string()),
+            award: Some(vec!["1234".to_string()]),
+            doi_asserted_by: None,
+        }]);
+        work
+    }
+
+    #[test]
+    fn normalizes_identifiers() {
+        assert_eq!("10.5555/abc", normalize_doi("https://doi.org/10.5555/ABC"));
+        assert_eq!("0003-066X", normalize_issn("0003066x"));
+        assert_eq!(
+            "0000-0002-0270-171X",
+            normalize_orcid("http://orcid.org/0000-0002-0270-171x")
+        );
+    }
+
+    #[test]
+    fn lookups() {
+        let mut store = Store::in_memory().unwrap();
+        assert_eq!(
+            2,
+            store
+                .save_works(&[work("10.5555/1", 1), work("10.5555/2", 1)])
+                .unwrap()
+        );
+
+        assert_eq!(
+            "10.5555/1",
+            store
+                .work("https://doi.org/10.5555/1")
+                .unwrap()
+                .unwrap()
+                .doi
+        );
+        assert!(store.work("10.5555/3").unwrap().is_none());
+        assert_eq!(2, store.works_by_issn("0003-066x").unwrap().len());
+        assert_eq!(
+            2,
+            store.works_by_orcid("0000-0002-0270-1711").unwrap().len()
+        );
+        assert_eq!(
+            2,
+            store.works_by_funder("10.13039/100000001").unwrap().len()
+        );
+
+        let issns: i64 = store
+            .connection()
+            .query_row("SELECT COUNT(*) FROM work_issns", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(2, issns);
+    }
+
+    #[test]
+    fn keeps_later_indexed_works() {
+        let mut store = Store::in_memory().unwrap();
+        assert!(store.save_work(&work("10.5555/1", 2)).unwrap());
+
+        let mut older = work("10.5555/1", 1);
+        older.author = None;
+        assert!(!store.save_work(&older).unwrap());
+        assert_eq!(
+            1,
+            store.works_by_orcid("0000-0002-0270-1711").unwrap().len()
+        );
+
+        let mut newer = work("10.5555/1", 3);
+        newer.author = None;
+        assert!(store.save_work(&newer).unwrap());
+        assert!(store
+            .works_by_orcid("0000-0002-0270-1711")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn journals_by_any_issn() {
+        let mut store = Store::in_memory().unwrap();
+        let mut journal: Journal = serde_json::from_str(
+            r#"{"title":"American Psychologist","publisher":"APA","subjects":[],"ISSN":["0003-066X"],"issn-type":[]}"#,
+        )
+        .unwrap();
+        store.save_journal(&journal).unwrap();
+        journal.issn.push("1935-990X".to_string());
+        journal.title = Some("Am. Psychologist".to_string());
+        store.save_journal(&journal).unwrap();
+
+        let stored = store.journal("1935990x").unwrap().unwrap();
+        assert_eq!(Some("Am. Psychologist".to_string()), stored.title);
+        let journals: i64 = store
+            .connection()
+            .query_row("SELECT COUNT(*) FROM journals", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(1, journals);
+    }
+}
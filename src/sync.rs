@@ -0,0 +1,413 @@
+use crate::asynchronous::AsyncCrossref;
+use crate::error::Result;
+use crate::response::work::Date;
+use crate::response::Work;
+use crate::{WorkListQuery, WorksFilter};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures::stream::{BoxStream, Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The filter a `WorksSync` selects the changed works with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncField {
+    /// works that were indexed since the last sync, `from-index-date`
+    ///
+    /// This includes works whose metadata changed on the crossref side, like new citation counts.
+    IndexDate,
+    /// works that were deposited by their member since the last sync, `from-update-date`
+    UpdateDate,
+}
+
+impl SyncField {
+    /// the time the `work` changed according to this field, its `indexed` or `deposited` date
+    fn changed_at(self, work: &Work) -> Option<DateTime<Utc>> {
+        match self {
+            SyncField::IndexDate => timestamp(&work.indexed),
+            SyncField::UpdateDate => work.deposited.as_ref().and_then(timestamp),
+        }
+    }
+}
+
+/// Whether a synced `Work` is new to the mirror or an update of a work it already has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// the work was created after the last sync
+    New,
+    /// the work existed before the last sync and was changed since
+    Updated,
+}
+
+/// A `Work` that changed since the last sync
+#[derive(Debug, Clone)]
+pub struct SyncedWork {
+    /// whether the work is new or updated
+    pub change: Change,
+    /// the work itself
+    pub work: Work,
+}
+
+/// Pages through all works of a query that changed since the last sync
+///
+/// The filters crossref offers only take days, so the query is restricted to the day of the last sync
+/// and works that did not change after the exact time are skipped. A work changed at the date the
+/// `SyncField` filters on, `indexed` for `SyncField::IndexDate` and `deposited` for `SyncField::UpdateDate`.
+/// It is `Change::New` if it was `created` after the last sync, or `deposited` if it has no `created` date,
+/// and `Change::Updated` otherwise.
+///
+/// Once all works were returned, [`SyncStream::high_water_mark`] is the time to start the next sync from.
+/// It is the time the sync started minus the `overlap`, not the latest change of the returned works, since a work
+/// can become visible in the api some time after it was indexed. Works changed within the overlap are returned
+/// again by the next sync. If the sync failed, the high-water mark stays at the last sync, so the next run syncs
+/// the same works again.
+///
+/// # Example
+///
+/// ```edition2018
+/// # #[cfg(feature = "blocking")] {
+/// use crossref::{Change, Crossref, Members, WorksQuery, WorksSync};
+/// use chrono::{DateTime, Utc};
+/// # fn run() -> Result<(), crossref::Error> {
+/// let client = Crossref::builder().build()?;
+///
+/// let last_sync: DateTime<Utc> = std::fs::read_to_string("last-sync")?
+///     .trim()
+///     .parse()
+///     .map_err(|_| crossref::ErrorKind::Config { msg: "invalid last sync".to_string() })?;
+///
+/// let mut works = client.sync(WorksSync::new(
+///     WorksQuery::default().into_combined_query::<Members>("98"),
+///     last_sync,
+/// ));
+/// for work in &mut works {
+///     let work = work?;
+///     match work.change {
+///         Change::New => println!("new {}", work.work.doi),
+///         Change::Updated => println!("updated {}", work.work.doi),
+///     }
+/// }
+/// std::fs::write("last-sync", works.high_water_mark().to_rfc3339())?;
+/// # Ok(())
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WorksSync {
+    /// the query whose works are synced
+    pub query: WorkListQuery,
+    /// the time of the last sync
+    pub since: DateTime<Utc>,
+    /// the filter that selects the changed works
+    pub field: SyncField,
+    /// how far the next sync reaches back before the start of this one
+    pub overlap: Duration,
+}
+
+impl WorksSync {
+    /// the default overlap, longer than crossref usually takes to make an indexed work visible
+    pub const DEFAULT_OVERLAP: Duration = Duration::hours(1);
+
+    /// create a new sync of the works of the `query` that changed after `since`
+    pub fn new<T: Into<WorkListQuery>>(query: T, since: DateTime<Utc>) -> Self {
+        WorksSync {
+            query: query.into(),
+            since,
+            field: SyncField::IndexDate,
+            overlap: WorksSync::DEFAULT_OVERLAP,
+        }
+    }
+
+    /// set how far the next sync reaches back before the start of this one, `DEFAULT_OVERLAP` by default
+    pub fn overlap(mut self, overlap: Duration) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// set the filter that selects the changed works, `SyncField::IndexDate` by default
+    pub fn field(mut self, field: SyncField) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// the query restricted to the works changed since the last sync
+    fn changed_query(&self) -> WorkListQuery {
+        let day = self.since.date_naive();
+        let filter = match self.field {
+            SyncField::IndexDate => WorksFilter::FromIndexDate(day),
+            SyncField::UpdateDate => WorksFilter::FromUpdateDate(day),
+        };
+        let mut query = self.query.clone();
+        query.query_mut().filter.push(filter);
+        query
+    }
+}
+
+/// A stream over the works that changed since the last sync
+///
+/// Created by [`AsyncCrossref::sync`]. The stream has to be polled from within a tokio runtime.
+pub struct SyncStream {
+    /// the time of the last sync
+    since: DateTime<Utc>,
+    /// the date the works changed at
+    field: SyncField,
+    /// the time to start the next sync from once this one is complete
+    next_since: DateTime<Utc>,
+    /// the works of all pages
+    works: BoxStream<'static, Result<Work>>,
+    /// whether all works were returned without an error
+    completed: bool,
+    /// whether a page failed
+    failed: bool,
+}
+
+impl SyncStream {
+    pub(crate) fn new(client: &AsyncCrossref, sync: WorksSync) -> Self {
+        SyncStream {
+            since: sync.since,
+            field: sync.field,
+            next_since: (Utc::now() - sync.overlap).max(sync.since),
+            works: client
+                .deep_page(sync.changed_query())
+                .into_work_stream()
+                .boxed(),
+            completed: false,
+            failed: false,
+        }
+    }
+
+    /// whether all changed works were returned
+    pub fn is_complete(&self) -> bool {
+        self.completed
+    }
+
+    /// the time to start the next sync from
+    ///
+    /// This is the time this sync started minus the overlap once the sync is complete, and the time of the
+    /// last sync before that.
+    pub fn high_water_mark(&self) -> DateTime<Utc> {
+        if self.completed {
+            self.next_since
+        } else {
+            self.since
+        }
+    }
+}
+
+impl Stream for SyncStream {
+    type Item = Result<SyncedWork>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let work = match this.works.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(work))) => work,
+                Poll::Ready(Some(Err(err))) => {
+                    this.failed = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                Poll::Ready(None) => {
+                    this.completed = !this.failed;
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            match this.field.changed_at(&work) {
+                Some(changed) if changed > this.since => {}
+                // changed earlier on the day of the last sync and already synced then
+                _ => continue,
+            }
+            let created = work.created.as_ref().or(work.deposited.as_ref());
+            let change = match created.and_then(timestamp) {
+                Some(created) if created > this.since => Change::New,
+                _ => Change::Updated,
+            };
+            return Poll::Ready(Some(Ok(SyncedWork { change, work })));
+        }
+    }
+}
+
+/// the `timestamp` of the `date`, in milliseconds since the unix epoch
+fn timestamp(date: &Date) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(date.timestamp as i64).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::tests::work_json;
+    use crate::{CrossrefRoute, InMemoryTransport, WorkResultControl, WorksQuery};
+    use futures::TryStreamExt;
+
+    /// a work that was created and indexed at the milliseconds since the epoch
+    fn work(doi: &str, created: u64, indexed: u64) -> String {
+        work_json(doi).replace(
+            r#""indexed":{"date-parts":[[2019,1,1]],"date-time":"2019-01-01T00:00:00Z","timestamp":1546300800000}"#,
+            &format!(
+                r#""created":{{"date-parts":[[2019]],"date-time":"","timestamp":{}}},"indexed":{{"date-parts":[[2019]],"date-time":"","timestamp":{}}}"#,
+                created, indexed
+            ),
+        )
+    }
+
+    /// the `work` deposited at the milliseconds since the epoch
+    fn deposited(work: String, deposited: u64) -> String {
+        work.replace(
+            r#""issued":"#,
+            &format!(
+                r#""deposited":{{"date-parts":[[2019]],"date-time":"","timestamp":{}}},"issued":"#,
+                deposited
+            ),
+        )
+    }
+
+    fn page(items: &[String], next_cursor: &str) -> String {
+        format!(
+            r#"{{"status":"ok","message-type":"work-list","message-version":"1.0.0","message":{{"facets":{{}},"total-results":{},"items-per-page":20,"items":[{}],"next-cursor":"{}"}}}}"#,
+            items.len(),
+            items.join(","),
+            next_cursor
+        )
+    }
+
+    #[tokio::test]
+    async fn classifies_changed_works() {
+        // 2019-01-01T12:00:00Z
+        let since = Utc.timestamp_millis_opt(1_546_344_000_000).unwrap();
+        let sync = WorksSync::new(WorksQuery::new("ontologies"), since);
+
+        let mut first = sync.changed_query();
+        first.query_mut().result_control = Some(WorkResultControl::new_cursor());
+        let mut last = sync.changed_query();
+        last.query_mut().result_control = Some(WorkResultControl::cursor("next"));
+        assert!(first
+            .route()
+            .unwrap()
+            .contains("filter=from-index-date:2019-01-01"));
+
+        let transport = InMemoryTransport::new()
+            .with_json(
+                first.route().unwrap(),
+                page(
+                    &[
+                        // synced the last time
+                        work("10.5555/1", 1_546_300_800_000, 1_546_340_000_000),
+                        work("10.5555/2", 1_546_300_800_000, 1_546_350_000_000),
+                        work("10.5555/3", 1_546_345_000_000, 1_546_345_000_000),
+                    ],
+                    "next",
+                ),
+            )
+            .with_json(last.route().unwrap(), page(&[], "next"));
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(transport)
+            .build_async()
+            .unwrap();
+
+        let started = Utc::now();
+        let mut works = client.sync(sync);
+        assert_eq!(since, works.high_water_mark());
+        let changes: Vec<_> = (&mut works)
+            .map_ok(|work| (work.work.doi, work.change))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![
+                ("10.5555/2".to_string(), Change::Updated),
+                ("10.5555/3".to_string(), Change::New)
+            ],
+            changes
+        );
+        assert!(works.is_complete());
+        // not the latest change, a work indexed before it can still become visible after this sync
+        let high_water_mark = works.high_water_mark();
+        assert!(high_water_mark >= started - WorksSync::DEFAULT_OVERLAP);
+        assert!(high_water_mark <= Utc::now() - WorksSync::DEFAULT_OVERLAP);
+    }
+
+    #[tokio::test]
+    async fn update_date_follows_deposits() {
+        let since = Utc.timestamp_millis_opt(1_546_344_000_000).unwrap();
+        let sync =
+            WorksSync::new(WorksQuery::new("ontologies"), since).field(SyncField::UpdateDate);
+
+        let mut first = sync.changed_query();
+        first.query_mut().result_control = Some(WorkResultControl::new_cursor());
+        let mut last = sync.changed_query();
+        last.query_mut().result_control = Some(WorkResultControl::cursor("next"));
+        assert!(first
+            .route()
+            .unwrap()
+            .contains("filter=from-update-date:2019-01-01"));
+
+        let transport = InMemoryTransport::new()
+            .with_json(
+                first.route().unwrap(),
+                page(
+                    &[
+                        // reindexed, but deposited before the last sync
+                        deposited(
+                            work("10.5555/1", 1_546_300_800_000, 1_546_360_000_000),
+                            1_546_340_000_000,
+                        ),
+                        deposited(
+                            work("10.5555/2", 1_546_300_800_000, 1_546_360_000_000),
+                            1_546_350_000_000,
+                        ),
+                    ],
+                    "next",
+                ),
+            )
+            .with_json(last.route().unwrap(), page(&[], "next"));
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(transport)
+            .build_async()
+            .unwrap();
+
+        let mut works = client.sync(sync);
+        let dois: Vec<_> = (&mut works)
+            .map_ok(|work| work.work.doi)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(vec!["10.5555/2".to_string()], dois);
+        assert!(works.is_complete());
+    }
+
+    #[tokio::test]
+    async fn high_water_mark_stays_after_recent_sync() {
+        let since = Utc::now() - Duration::minutes(5);
+        let sync = WorksSync::new(WorksQuery::new("ontologies"), since);
+        let mut first = sync.changed_query();
+        first.query_mut().result_control = Some(WorkResultControl::new_cursor());
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(InMemoryTransport::new().with_json(first.route().unwrap(), page(&[], "")))
+            .build_async()
+            .unwrap();
+
+        let mut works = client.sync(sync);
+        assert!(works.next().await.is_none());
+        assert!(works.is_complete());
+        // the overlap reaches back before the last sync, which already covered that time
+        assert_eq!(since, works.high_water_mark());
+    }
+
+    #[tokio::test]
+    async fn failed_sync_keeps_high_water_mark() {
+        let since = Utc.timestamp_millis_opt(1_546_344_000_000).unwrap();
+        let client = AsyncCrossref::builder()
+            .polite("polite@example.com")
+            .transport(InMemoryTransport::new())
+            .build_async()
+            .unwrap();
+
+        let mut works = client.sync(WorksSync::new(WorksQuery::new("ontologies"), since));
+        assert!(works.next().await.unwrap().is_err());
+        assert!(works.next().await.is_none());
+        assert!(!works.is_complete());
+        assert_eq!(since, works.high_water_mark());
+    }
+}
@@ -0,0 +1,417 @@
+use crate::error::{ErrorKind, Result};
+use futures::future::BoxFuture;
+use reqwest::header::HeaderMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// A request the client wants to execute
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    /// the full url of the request, the base url of the client followed by the route
+    pub url: String,
+    /// the route of the request, like `/works?query=ontologies`, without the base url and its path
+    pub route: String,
+    /// the headers the client adds to each request, like `User-Agent`
+    pub headers: HeaderMap,
+}
+
+/// The raw response to a `TransportRequest`
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    /// the http status code
+    pub status: u16,
+    /// the response headers
+    pub headers: HeaderMap,
+    /// the response body
+    pub body: String,
+}
+
+impl TransportResponse {
+    /// a `200 OK` response with the `body`
+    pub fn ok<T: Into<String>>(body: T) -> Self {
+        TransportResponse::with_status(200, body)
+    }
+
+    /// a response with the `status` and the `body`
+    pub fn with_status<T: Into<String>>(status: u16, body: T) -> Self {
+        TransportResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// the response crossref sends for unknown routes
+    pub fn not_found() -> Self {
+        TransportResponse::with_status(404, "Resource not found.")
+    }
+}
+
+/// Executes the http requests of a client
+///
+/// By default the client uses the `ReqwestTransport`. For tests the `InMemoryTransport` can answer with canned responses
+/// instead, a custom transport can be set with [`crate::CrossrefBuilder::transport`].
+pub trait Transport: Debug + Send + Sync {
+    /// executes the `request` and returns the response
+    ///
+    /// Responses with any status are returned as `Ok`, errors are reserved for failed requests.
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>>;
+}
+
+/// A `Transport` that executes the requests with [reqwest](https://docs.rs/reqwest)
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    /// the reqwest client that handles the requests
+    pub client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// create a new transport that uses the `client`
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>> {
+        Box::pin(async move {
+            let resp = self
+                .client
+                .get(&request.url)
+                .headers(request.headers)
+                .send()
+                .await?;
+            let status = resp.status().as_u16();
+            let headers = resp.headers().clone();
+            let body = resp.text().await?;
+            Ok(TransportResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+/// A `Transport` that answers with responses registered for a route, without any network access
+///
+/// Routes are matched against the route of the request, without the base url of the client, for example
+/// `/works/10.1037/0003-066X.59.1.29`.
+/// If several responses are registered for a route, they are returned in order and the last one is repeated.
+/// Requests for unregistered routes are answered with `404 Resource not found.`, like crossref does.
+/// Clones share the registered responses and the recorded requests.
+///
+/// # Example
+///
+/// ```edition2018
+/// # #[cfg(feature = "blocking")] {
+/// use crossref::{Crossref, InMemoryTransport};
+/// # fn run() -> Result<(), crossref::Error> {
+/// let transport = InMemoryTransport::new().with_json(
+///     "/types/book",
+///     r#"{"status":"ok","message-type":"type","message-version":"1.0.0","message":{"id":"book","label":"Book"}}"#,
+/// );
+///
+/// let client = Crossref::builder().transport(transport.clone()).build()?;
+/// let book = client.type_(&crossref::Type::Book)?;
+///
+/// assert_eq!(1, transport.requests().len());
+/// # Ok(())
+/// # }
+/// # run().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryTransport {
+    /// the responses for each route
+    routes: Arc<Mutex<HashMap<String, VecDeque<TransportResponse>>>>,
+    /// all requests that were received
+    requests: Arc<Mutex<Vec<TransportRequest>>>,
+}
+
+impl InMemoryTransport {
+    /// create a new transport without any routes
+    pub fn new() -> Self {
+        InMemoryTransport::default()
+    }
+
+    /// register a `200 OK` response with the json `body` for the `route`
+    pub fn with_json<R: Into<String>, B: Into<String>>(self, route: R, body: B) -> Self {
+        self.insert(route, TransportResponse::ok(body));
+        self
+    }
+
+    /// register the `response` for the `route`, after the responses that are already registered for it
+    pub fn insert<R: Into<String>>(&self, route: R, response: TransportResponse) {
+        lock(&self.routes)
+            .entry(route.into())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// all requests this transport received so far
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        lock(&self.requests).clone()
+    }
+
+    /// the response for the `route`
+    fn respond(&self, route: &str) -> TransportResponse {
+        let mut routes = lock(&self.routes);
+        match routes.get_mut(route) {
+            Some(responses) if responses.len() > 1 => responses.pop_front().unwrap(),
+            Some(responses) => responses
+                .front()
+                .cloned()
+                .unwrap_or_else(TransportResponse::not_found),
+            None => TransportResponse::not_found(),
+        }
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>> {
+        let response = self.respond(&request.route);
+        lock(&self.requests).push(request);
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+/// Whether a `FixtureTransport` records or replays responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// execute the requests and write their responses to the fixture directory
+    Record,
+    /// answer the requests only from the fixture directory
+    Replay,
+}
+
+/// A `Transport` that records responses to a directory and replays them later
+///
+/// Each fixture is a file that contains the raw response body for a single request url.
+/// The file name is derived from the route of the request, so fixtures don't depend on the base url of the client,
+/// not even on its path.
+///
+/// When recording, the requests are executed by another transport. Only responses with status `200` or `404`
+/// are recorded, since replaying answers with the recorded body as `200`. So a request that is retried is recorded
+/// once it succeeds, and a `304` revalidation of a cached response keeps the fixture of the first request.
+/// When replaying, a request without a fixture fails with a `MissingFixture` error.
+///
+/// # Example
+///
+/// ```edition2018
+/// # #[cfg(feature = "blocking")] {
+/// use crossref::{Crossref, WorksQuery};
+/// # fn run() -> Result<(), crossref::Error> {
+/// // record once with access to the api
+/// let client = Crossref::builder().record("tests/fixtures").build()?;
+/// let works: Vec<_> = client.deep_page(WorksQuery::new("ontologies")).take(2).collect();
+///
+/// // run the same requests without the network
+/// let client = Crossref::builder().replay("tests/fixtures").build()?;
+/// let replayed: Vec<_> = client.deep_page(WorksQuery::new("ontologies")).take(2).collect();
+/// # Ok(())
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixtureTransport {
+    /// the directory the fixtures are stored in
+    dir: PathBuf,
+    /// whether to record or replay
+    mode: FixtureMode,
+    /// executes the requests when recording
+    inner: Option<Arc<dyn Transport>>,
+}
+
+impl FixtureTransport {
+    /// record the responses of the `inner` transport to the `dir`
+    pub fn record<P: Into<PathBuf>, T: Transport + 'static>(dir: P, inner: T) -> Self {
+        FixtureTransport::recording(dir.into(), Arc::new(inner))
+    }
+
+    /// answer all requests with the responses recorded in `dir`
+    pub fn replay<P: Into<PathBuf>>(dir: P) -> Self {
+        FixtureTransport {
+            dir: dir.into(),
+            mode: FixtureMode::Replay,
+            inner: None,
+        }
+    }
+
+    pub(crate) fn recording(dir: PathBuf, inner: Arc<dyn Transport>) -> Self {
+        FixtureTransport {
+            dir,
+            mode: FixtureMode::Record,
+            inner: Some(inner),
+        }
+    }
+
+    /// the directory the fixtures are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// whether this transport records or replays
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    /// the file of the fixture for the `route`
+    pub fn fixture_path(&self, route: &str) -> PathBuf {
+        self.dir.join(fixture_name(route))
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>> {
+        Box::pin(async move {
+            let path = self.fixture_path(&request.route);
+            match &self.inner {
+                Some(inner) => {
+                    let url = request.url.clone();
+                    let resp = inner.send(request).await?;
+                    if resp.status == 200 || resp.status == 404 {
+                        fs::create_dir_all(&self.dir)?;
+                        fs::write(&path, &resp.body)?;
+                    }
+                    Ok(resp)
+                }
+                None => match fs::read_to_string(&path) {
+                    Ok(body) => Ok(TransportResponse::ok(body)),
+                    Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                        Err(ErrorKind::MissingFixture {
+                            url: request.url,
+                            path: path.display().to_string(),
+                        }
+                        .into())
+                    }
+                    Err(err) => Err(err.into()),
+                },
+            }
+        })
+    }
+}
+
+/// a file name for the `route` that is readable and unique
+pub(crate) fn fixture_name(route: &str) -> String {
+    // keep file names short, the hash tells routes with the same prefix apart
+    const MAX_PREFIX: usize = 64;
+    let prefix: String = route
+        .trim_start_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_PREFIX)
+        .collect();
+    format!("{}-{:016x}.json", prefix, fnv1a(route.as_bytes()))
+}
+
+/// the 64 bit FNV-1a hash, which unlike the std hashers is guaranteed to be stable
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// a minimal crossref `Work` with the `doi`
+    pub(crate) fn work_json(doi: &str) -> String {
+        format!(
+            r#"{{"publisher":"Test","title":["{doi}"],"reference-count":0,"references-count":0,"is-referenced-by-count":0,"source":"Crossref","prefix":"10.5555","DOI":"{doi}","URL":"https://doi.org/{doi}","member":"1","type":"journal-article","indexed":{{"date-parts":[[2019,1,1]],"date-time":"2019-01-01T00:00:00Z","timestamp":1546300800000}},"issued":{{"date-parts":[[2018]]}}}}"#,
+            doi = doi
+        )
+    }
+
+    /// a crossref response with a single `Work`
+    pub(crate) fn work_response(doi: &str) -> String {
+        format!(
+            r#"{{"status":"ok","message-type":"work","message-version":"1.0.0","message":{}}}"#,
+            work_json(doi)
+        )
+    }
+
+    /// a crossref response with a `WorkList` of the `dois` that points to the `next_cursor`
+    pub(crate) fn work_list_response(dois: &[&str], next_cursor: Option<&str>) -> String {
+        let items: Vec<_> = dois.iter().map(|doi| work_json(doi)).collect();
+        let cursor = next_cursor
+            .map(|c| format!(r#","next-cursor":"{}""#, c))
+            .unwrap_or_default();
+        format!(
+            r#"{{"status":"ok","message-type":"work-list","message-version":"1.0.0","message":{{"facets":{{}},"total-results":{},"items-per-page":20,"items":[{}]{}}}}}"#,
+            dois.len(),
+            items.join(","),
+            cursor
+        )
+    }
+
+    #[test]
+    fn responses_in_order() {
+        let transport = InMemoryTransport::new();
+        transport.insert("/types", TransportResponse::with_status(503, ""));
+        transport.insert("/types", TransportResponse::ok("{}"));
+
+        assert_eq!(503, transport.respond("/types").status);
+        assert_eq!(200, transport.respond("/types").status);
+        assert_eq!(200, transport.respond("/types").status);
+        assert_eq!(404, transport.respond("/members").status);
+    }
+
+    #[tokio::test]
+    async fn records_only_replayable_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = InMemoryTransport::new();
+        inner.insert("/types/book", TransportResponse::with_status(304, ""));
+        inner.insert("/types/book", TransportResponse::with_status(503, ""));
+        inner.insert("/types/book", TransportResponse::ok("{}"));
+        let transport = FixtureTransport::record(dir.path(), inner);
+        let request = || TransportRequest {
+            url: "https://api.crossref.org/types/book".to_string(),
+            route: "/types/book".to_string(),
+            headers: HeaderMap::new(),
+        };
+
+        let path = transport.fixture_path(&request().route);
+        transport.send(request()).await.unwrap();
+        transport.send(request()).await.unwrap();
+        assert!(!path.exists());
+        transport.send(request()).await.unwrap();
+        assert_eq!("{}", fs::read_to_string(&path).unwrap());
+
+        transport
+            .send(TransportRequest {
+                url: "https://api.crossref.org/types/unknown".to_string(),
+                route: "/types/unknown".to_string(),
+                headers: HeaderMap::new(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(2, fs::read_dir(dir.path()).unwrap().count());
+    }
+
+    #[test]
+    fn fixture_names() {
+        assert!(fixture_name("/works/10.5555/12345678").starts_with("works_10.5555_12345678-"));
+        assert_ne!(
+            fixture_name("/works?query=a_b"),
+            fixture_name("/works?query=a&b")
+        );
+        let long = format!("/works?query={}", "a".repeat(500));
+        assert!(fixture_name(&long).len() < 100);
+    }
+}